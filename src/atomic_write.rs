@@ -0,0 +1,45 @@
+//! Write files by renaming temporary files over them
+
+use std::{
+    fs::{canonicalize, metadata},
+    io::{self, Write},
+    path::Path,
+};
+use tempfile::Builder;
+
+/// Writes `contents` to the file at `path` by writing a temporary file in the same directory and
+/// renaming it over the file
+///
+/// The file's permissions are preserved. If `path` is a symbolic link, the file the link refers to
+/// is replaced, and the link is left intact.
+///
+/// Like [`std::fs::write`], this function returns an [`io::Result`] so that callers can inspect
+/// the error's kind.
+#[allow(clippy::disallowed_methods)]
+pub fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    // smoelius: Canonicalizing the path resolves symbolic links, so that the link's target is
+    // replaced rather than the link itself.
+    let path = canonicalize(path)?;
+    let permissions = metadata(&path)?.permissions();
+    let (Some(parent), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return Err(io::Error::other(format!(
+            "`{}` has no parent or file name",
+            path.display()
+        )));
+    };
+    // smoelius: The temporary file is given a random name and is created exclusively, so that it
+    // cannot clobber or write through an existing file. If an error occurs, the temporary file is
+    // removed when dropped.
+    let mut tempfile = Builder::new()
+        .prefix(&format!(
+            ".{}.rustdoc-prettier-",
+            file_name.to_string_lossy()
+        ))
+        .suffix(".tmp")
+        .tempfile_in(parent)?;
+    tempfile.write_all(contents.as_bytes())?;
+    tempfile.as_file().set_permissions(permissions)?;
+    tempfile.as_file().sync_all()?;
+    tempfile.persist(&path)?;
+    Ok(())
+}
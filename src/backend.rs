@@ -0,0 +1,110 @@
+//! Markdown formatters that doc comments can be formatted with
+//!
+//! A backend formats markdown so that its lines are no wider than a given width. Formatting is
+//! split into two steps, [`Backend::start`] and [`Job::finish`], so that a backend that runs an
+//! external program can start the program before the markdown is available.
+
+use super::{Options, exit_status_adverbial};
+use anyhow::{Result, anyhow, bail, ensure};
+use elaborate::std::{io::WriteContext, process::ChildContext};
+use std::{path::Path, process::Child};
+
+mod dprint;
+
+mod native;
+
+mod prettier;
+pub use prettier::{
+    check_version as check_prettier_version, command as prettier_command,
+    find_config_path as find_prettier_config_path, output as prettier_output,
+    resolve as resolve_prettier, stdin_filepath,
+};
+
+pub trait Backend: Sync {
+    /// The name by which the backend is selected
+    fn name(&self) -> &'static str;
+
+    /// Returns an error if the backend cannot be used to format the files at `paths`, e.g.,
+    /// because a program it runs is not installed
+    fn check(&self, opts: &Options, paths: &[&Path]) -> Result<()>;
+
+    /// Starts formatting markdown from the file at `path` so that its lines are no wider than
+    /// `width`
+    ///
+    /// If `width` is `None`, the backend's default wrapping behavior is used. The markdown is
+    /// passed to the returned [`Job`].
+    fn start(&self, opts: &Options, path: &Path, width: Option<usize>) -> Result<Box<dyn Job>>;
+}
+
+pub trait Job: Send + Sync {
+    /// Returns `markdown` formatted
+    fn finish(self: Box<Self>, markdown: &str) -> Result<String>;
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Kind {
+    #[default]
+    Prettier,
+    Native,
+    Dprint,
+}
+
+impl Kind {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "prettier" => Ok(Self::Prettier),
+            "native" => Ok(Self::Native),
+            "dprint" => Ok(Self::Dprint),
+            _ => bail!("unknown backend: {s}"),
+        }
+    }
+
+    pub fn backend(self) -> &'static dyn Backend {
+        match self {
+            Self::Prettier => &prettier::Prettier,
+            Self::Native => &native::Native,
+            Self::Dprint => &dprint::Dprint,
+        }
+    }
+}
+
+/// A [`Job`] that writes the markdown to a child process's standard input, and reads the formatted
+/// markdown from the process's standard output
+struct ChildJob {
+    /// The name of the program the child runs, for use in error messages
+    program: &'static str,
+    child: Child,
+    /// Whether to return an error if the program's output differs from its input
+    check: bool,
+}
+
+impl Job for ChildJob {
+    fn finish(self: Box<Self>, markdown: &str) -> Result<String> {
+        let Self {
+            program,
+            mut child,
+            check,
+        } = *self;
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("child has no stdin"))?;
+
+        stdin.write_all_wc(markdown.as_bytes())?;
+        drop(stdin);
+
+        let output = child.wait_with_output_wc()?;
+        ensure!(
+            output.status.success(),
+            "`{program}` exited {}",
+            exit_status_adverbial(output.status)
+        );
+
+        let formatted = String::from_utf8(output.stdout)?;
+        ensure!(
+            !check || formatted == markdown,
+            "markdown would be reformatted"
+        );
+        Ok(formatted)
+    }
+}
@@ -0,0 +1,137 @@
+//! A backend that runs `dprint fmt --stdin`
+//!
+//! Like `prettier`'s `--stdin-filepath`, the path passed to `--stdin` is the source file's path
+//! with `.rustdoc.md` appended, so that dprint's `includes` and `excludes` patterns match it as
+//! they would a markdown file beside the source file.
+//!
+//! dprint reads its configuration from a `dprint.json` file in the source file's directory or its
+//! nearest ancestor, which must include dprint's markdown plugin. When a width is given, a
+//! configuration file that extends the discovered one and sets the markdown plugin's `lineWidth`
+//! is generated in a temporary directory. The generated file is removed when the job finishes.
+
+use super::{Backend, ChildJob, Job, stdin_filepath};
+use crate::{Options, find_nearest, parent_dir, program_version};
+use anyhow::{Result, bail};
+use elaborate::std::{io::WriteContext, process::CommandContext};
+use serde_json::json;
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+use tempfile::{Builder, NamedTempFile};
+
+const DPRINT: &str = "dprint";
+
+const CONFIG_FILE_NAMES: [&str; 4] = [
+    "dprint.json",
+    "dprint.jsonc",
+    ".dprint.json",
+    ".dprint.jsonc",
+];
+
+pub struct Dprint;
+
+/// A [`ChildJob`] that holds the configuration file generated for it, if any, until it finishes
+struct DprintJob {
+    job: ChildJob,
+    _generated_config: Option<NamedTempFile>,
+}
+
+impl Job for DprintJob {
+    fn finish(self: Box<Self>, markdown: &str) -> Result<String> {
+        Box::new(self.job).finish(markdown)
+    }
+}
+
+impl Backend for Dprint {
+    fn name(&self) -> &'static str {
+        "dprint"
+    }
+
+    fn check(&self, _opts: &Options, paths: &[&Path]) -> Result<()> {
+        let dirs = paths
+            .iter()
+            .map(|path| parent_dir(path))
+            .collect::<BTreeSet<_>>();
+        for dir in dirs {
+            config_file(dir)?;
+        }
+        program_version(DPRINT).map(|_| ())
+    }
+
+    fn start(&self, opts: &Options, path: &Path, width: Option<usize>) -> Result<Box<dyn Job>> {
+        let mut command = Command::new(DPRINT);
+        command.arg("fmt");
+        let generated_config = width
+            .map(|width| generated_config(&config_file(parent_dir(path))?, width))
+            .transpose()?;
+        if let Some(generated_config) = &generated_config {
+            command.arg("--config");
+            command.arg(generated_config.path());
+        }
+        // smoelius: `dprint fmt` has no `--check` option. So the check is performed by comparing
+        // dprint's output to its input.
+        let mut check = false;
+        for arg in &opts.args {
+            if arg == "--check" {
+                check = true;
+            } else {
+                command.arg(arg);
+            }
+        }
+        // smoelius: dprint selects a plugin by the extension of the path passed to `--stdin`. So
+        // markdown from standard input is given a made-up path.
+        command.arg("--stdin");
+        command.arg(stdin_filepath(path).unwrap_or_else(|| PathBuf::from("file.md")));
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let child = command.spawn_wc()?;
+        Ok(Box::new(DprintJob {
+            job: ChildJob {
+                program: "dprint",
+                child,
+                check,
+            },
+            _generated_config: generated_config,
+        }))
+    }
+}
+
+/// Returns the dprint configuration file in `dir` or its nearest ancestor
+fn config_file(dir: &Path) -> Result<PathBuf> {
+    if let Some(path) = find_nearest(dir, &CONFIG_FILE_NAMES)? {
+        return Ok(path);
+    }
+    bail!(
+        "found no dprint configuration file (e.g., `dprint.json`) in `{}` or its ancestors; the \
+         dprint backend requires one that includes dprint's markdown plugin",
+        dir.display()
+    )
+}
+
+/// Returns a configuration file that extends `extends`, and that sets the markdown plugin's
+/// `lineWidth` to `width`
+///
+/// The file is removed when the returned value is dropped.
+fn generated_config(extends: &Path, width: usize) -> Result<NamedTempFile> {
+    // smoelius: `textWrap` is set for the same reason that `--prose-wrap=always` is passed to
+    // `prettier`.
+    let contents = json!({
+        "extends": extends.to_string_lossy(),
+        "markdown": {
+            "lineWidth": width,
+            "textWrap": "always",
+        },
+    })
+    .to_string();
+    let mut tempfile = Builder::new()
+        .prefix(&format!("dprint-{width}-"))
+        .suffix(".json")
+        .tempfile()?;
+    tempfile.write_all_wc(contents.as_bytes())?;
+    tempfile.flush_wc()?;
+    Ok(tempfile)
+}
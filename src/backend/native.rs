@@ -0,0 +1,346 @@
+//! A backend that formats markdown without running an external program
+//!
+//! The markdown is parsed with [`pulldown_cmark`]. The prose of each paragraph is refilled so that
+//! its lines are no wider than the given width, and blank lines are normalized. All other markdown
+//! is left as it is written. See the "Native backend" section of the README for how the output
+//! differs from `prettier`'s.
+
+use super::{Backend, Job};
+use crate::Options;
+use anyhow::{Result, bail, ensure};
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use std::{ops::Range, path::Path};
+
+pub struct Native;
+
+impl Backend for Native {
+    fn name(&self) -> &'static str {
+        "native"
+    }
+
+    fn check(&self, _opts: &Options, _paths: &[&Path]) -> Result<()> {
+        Ok(())
+    }
+
+    fn start(&self, opts: &Options, _path: &Path, width: Option<usize>) -> Result<Box<dyn Job>> {
+        Ok(Box::new(NativeJob {
+            args: opts.args.clone(),
+            width,
+        }))
+    }
+}
+
+struct NativeJob {
+    args: Vec<String>,
+    width: Option<usize>,
+}
+
+impl Job for NativeJob {
+    fn finish(self: Box<Self>, markdown: &str) -> Result<String> {
+        let mut check = false;
+        for arg in &self.args {
+            if arg == "--check" {
+                check = true;
+            } else {
+                bail!("`{arg}` is not supported by the native backend");
+            }
+        }
+        let formatted = format(markdown, self.width);
+        ensure!(
+            !check || formatted == markdown,
+            "markdown would be reformatted"
+        );
+        Ok(formatted)
+    }
+}
+
+/// A run of prose, i.e., the inline content of a paragraph or of a tight list item
+struct Prose {
+    range: Range<usize>,
+    /// Whether the prose is inside a list item, block quote, or footnote definition
+    in_container: bool,
+    /// Ranges of inline code, inline HTML, and similar, within which lines must not be broken
+    atomic: Vec<Range<usize>>,
+    /// Offsets at which hard line breaks start
+    hard_breaks: Vec<usize>,
+}
+
+enum Token {
+    Word(String),
+    SoftBreak,
+    /// A hard line break, which is written as trailing spaces if the flag is set, or as a
+    /// backslash otherwise
+    HardBreak(bool),
+}
+
+/// Formats `markdown` so that its prose lines are no wider than `width`
+///
+/// If `width` is `None`, the prose's line breaks are preserved.
+pub fn format(markdown: &str, width: Option<usize>) -> String {
+    let mut refilled = String::new();
+    let mut offset = 0;
+    for prose in prose(markdown) {
+        let line_start = markdown[..prose.range.start]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        // smoelius: Outside of a container, indentation before a paragraph is insignificant and
+        // is removed.
+        let (start, prefix) = if prose.in_container {
+            (prose.range.start, &markdown[line_start..prose.range.start])
+        } else {
+            (line_start, "")
+        };
+        refilled.push_str(&markdown[offset..start]);
+        refilled.push_str(&fill(markdown, &prose, prefix, width));
+        offset = prose.range.end;
+    }
+    refilled.push_str(&markdown[offset..]);
+    normalize_blank_lines(&refilled)
+}
+
+fn parser(markdown: &str) -> Parser<'_> {
+    Parser::new_ext(
+        markdown,
+        pulldown_cmark::Options::ENABLE_FOOTNOTES
+            | pulldown_cmark::Options::ENABLE_STRIKETHROUGH
+            | pulldown_cmark::Options::ENABLE_TABLES
+            | pulldown_cmark::Options::ENABLE_TASKLISTS,
+    )
+}
+
+/// Returns the runs of prose in `markdown`, in order
+fn prose(markdown: &str) -> Vec<Prose> {
+    let mut prose = Vec::new();
+    let mut current: Option<Prose> = None;
+    let mut stack = Vec::new();
+    for (event, range) in parser(markdown).into_offset_iter() {
+        let (atomic, hard_break) = match &event {
+            Event::Start(tag) => {
+                let tag_end = tag.to_end();
+                if is_inline(tag_end) {
+                    stack.push(tag_end);
+                    (false, false)
+                } else {
+                    prose.extend(current.take());
+                    stack.push(tag_end);
+                    continue;
+                }
+            }
+            Event::End(tag_end) => {
+                stack.pop();
+                if !is_inline(*tag_end) {
+                    prose.extend(current.take());
+                }
+                continue;
+            }
+            Event::Code(_)
+            | Event::InlineHtml(_)
+            | Event::InlineMath(_)
+            | Event::FootnoteReference(_)
+            | Event::TaskListMarker(_) => (true, false),
+            Event::HardBreak => (false, true),
+            Event::Text(_) | Event::SoftBreak => (false, false),
+            Event::Html(_) | Event::DisplayMath(_) | Event::Rule => {
+                prose.extend(current.take());
+                continue;
+            }
+        };
+        let Some(block) = stack.iter().rev().find(|&&tag_end| !is_inline(tag_end)) else {
+            continue;
+        };
+        if !matches!(block, TagEnd::Paragraph | TagEnd::Item) {
+            continue;
+        }
+        let current = current.get_or_insert_with(|| Prose {
+            range: range.start..range.start,
+            in_container: stack.iter().any(|tag_end| {
+                matches!(
+                    tag_end,
+                    TagEnd::BlockQuote(_) | TagEnd::FootnoteDefinition | TagEnd::Item
+                )
+            }),
+            atomic: Vec::new(),
+            hard_breaks: Vec::new(),
+        });
+        current.range.end = current.range.end.max(range.end);
+        if atomic {
+            current.atomic.push(range.clone());
+        }
+        if hard_break {
+            current.hard_breaks.push(range.start);
+        }
+    }
+    prose.extend(current);
+    // smoelius: Trailing whitespace, e.g., a line ending, is left in place.
+    for prose in &mut prose {
+        let trimmed = markdown[prose.range.clone()].trim_end();
+        prose.range.end = prose.range.start + trimmed.len();
+    }
+    prose
+}
+
+fn is_inline(tag_end: TagEnd) -> bool {
+    matches!(
+        tag_end,
+        TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Superscript
+            | TagEnd::Subscript
+            | TagEnd::Link
+            | TagEnd::Image
+    )
+}
+
+/// Returns `prose` refilled, with each line after the first preceded by `prefix`'s continuation
+fn fill(markdown: &str, prose: &Prose, prefix: &str, width: Option<usize>) -> String {
+    // smoelius: A continuation line keeps the first line's block quote markers. Any list marker
+    // is replaced with spaces.
+    let continuation = prefix
+        .chars()
+        .map(|c| if c == '>' { '>' } else { ' ' })
+        .collect::<String>();
+    let first_width = width.map(|width| width.saturating_sub(prefix.chars().count()));
+    let continuation_width = width.map(|width| width.saturating_sub(continuation.chars().count()));
+
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for token in tokenize(markdown, prose) {
+        let width = if lines.is_empty() {
+            first_width
+        } else {
+            continuation_width
+        };
+        match token {
+            Token::Word(word) => {
+                if line.is_empty() {
+                    line = word;
+                } else if width
+                    .is_none_or(|width| line.chars().count() + 1 + word.chars().count() <= width)
+                    || !can_start_line(&word)
+                {
+                    line.push(' ');
+                    line.push_str(&word);
+                } else {
+                    lines.push(std::mem::replace(&mut line, word));
+                }
+            }
+            Token::SoftBreak => {
+                if width.is_none() && !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+            }
+            Token::HardBreak(spaces) => {
+                if spaces {
+                    line.push_str("  ");
+                }
+                lines.push(std::mem::take(&mut line));
+            }
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines.join(&format!("\n{continuation}"))
+}
+
+/// Splits `prose` into words and line breaks
+///
+/// Container markers at the start of each line are removed, as is whitespace between words.
+fn tokenize(markdown: &str, prose: &Prose) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut line_start = prose.range.start;
+    let mut char_indices = markdown[prose.range.clone()]
+        .char_indices()
+        .map(|(index, c)| (prose.range.start + index, c))
+        .peekable();
+    while let Some((index, c)) = char_indices.next() {
+        let atomic = prose.atomic.iter().any(|range| range.contains(&index));
+        if c == '\n' {
+            while char_indices
+                .next_if(|&(_, c)| c == ' ' || c == '\t' || c == '>')
+                .is_some()
+            {}
+            if atomic {
+                // smoelius: A line ending in inline code is equivalent to a space.
+                word.push(' ');
+            } else {
+                if !word.is_empty() {
+                    tokens.push(Token::Word(std::mem::take(&mut word)));
+                }
+                let hard_break = prose
+                    .hard_breaks
+                    .iter()
+                    .find(|&&offset| (line_start..index).contains(&offset));
+                tokens.push(match hard_break {
+                    Some(&offset) => Token::HardBreak(!markdown[offset..].starts_with('\\')),
+                    None => Token::SoftBreak,
+                });
+            }
+            line_start = index + 1;
+        } else if c.is_whitespace() && !atomic {
+            if !word.is_empty() {
+                tokens.push(Token::Word(std::mem::take(&mut word)));
+            }
+        } else {
+            word.push(c);
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(Token::Word(word));
+    }
+    tokens
+}
+
+/// Returns whether `word` can start a line without being interpreted as block syntax, e.g., as a
+/// list marker or heading
+fn can_start_line(word: &str) -> bool {
+    let ordered_list_marker = word
+        .strip_suffix(['.', ')'])
+        .is_some_and(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()));
+    !(ordered_list_marker
+        || word.chars().all(|c| "#*+-=_".contains(c))
+        || word.starts_with(['<', '>', '|'])
+        || word.starts_with("```")
+        || word.starts_with("~~~"))
+}
+
+/// Removes leading and trailing blank lines, and collapses consecutive blank lines outside of code
+/// blocks, HTML blocks, and tables
+fn normalize_blank_lines(markdown: &str) -> String {
+    let verbatim = parser(markdown)
+        .into_offset_iter()
+        .filter_map(|(event, range)| {
+            matches!(
+                event,
+                Event::Start(Tag::CodeBlock(_) | Tag::HtmlBlock | Tag::Table(_))
+            )
+            .then_some(range)
+        })
+        .collect::<Vec<_>>();
+
+    let mut normalized = String::new();
+    let mut blank = false;
+    let mut offset = 0;
+    for line in markdown.split_inclusive('\n') {
+        let line_range = offset..offset + line.len();
+        offset += line.len();
+        let is_verbatim = verbatim
+            .iter()
+            .any(|range| range.start < line_range.end && line_range.start < range.end);
+        if !is_verbatim && line.trim().is_empty() {
+            blank = true;
+            continue;
+        }
+        if blank && !normalized.is_empty() {
+            normalized.push('\n');
+        }
+        blank = false;
+        normalized.push_str(line);
+        if !line.ends_with('\n') {
+            normalized.push('\n');
+        }
+    }
+    normalized
+}
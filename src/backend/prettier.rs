@@ -0,0 +1,224 @@
+use super::{Backend, ChildJob, Job};
+use crate::{
+    Options, PrettierVersion, exit_status_adverbial, find_nearest, parent_dir, program_version,
+};
+use anyhow::{Context, Result, ensure};
+use elaborate::std::{env::var_os_wc, path::PathContext, process::CommandContext};
+use semver::Version;
+use std::{
+    collections::{BTreeMap, BTreeSet, btree_map::Entry},
+    env,
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::Mutex,
+};
+
+const PRETTIER: &str = if cfg!(windows) {
+    "prettier.cmd"
+} else {
+    "prettier"
+};
+
+/// The versions of the `prettier` programs that have been run, keyed by the programs' paths
+static VERSIONS: Mutex<BTreeMap<PathBuf, String>> = Mutex::new(BTreeMap::new());
+
+pub struct Prettier;
+
+impl Backend for Prettier {
+    fn name(&self) -> &'static str {
+        "prettier"
+    }
+
+    fn check(&self, opts: &Options, paths: &[&Path]) -> Result<()> {
+        // smoelius: The `prettier` that is run depends on the directory of the file being
+        // formatted. So the program resolved for each directory is checked, before any file is
+        // formatted. In particular, the version requirement is enforced even for files with no
+        // doc comments.
+        let dirs = paths
+            .iter()
+            .map(|path| parent_dir(path))
+            .collect::<BTreeSet<_>>();
+        let programs = dirs
+            .into_iter()
+            .map(|dir| resolve(opts, dir).program)
+            .collect::<BTreeSet<_>>();
+        for program in programs {
+            let version = version(&program)?;
+            check_version(opts, &program, &version)?;
+        }
+        Ok(())
+    }
+
+    fn start(&self, opts: &Options, path: &Path, width: Option<usize>) -> Result<Box<dyn Job>> {
+        let mut command = command(opts, path, width);
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let child = command.spawn_wc()?;
+        Ok(Box::new(ChildJob {
+            program: "prettier",
+            child,
+            // smoelius: `prettier` performs the check itself when given `--check`.
+            check: false,
+        }))
+    }
+}
+
+/// Returns the command that formats markdown from the file at `path` so that its lines are no
+/// wider than `width`
+pub fn command(opts: &Options, path: &Path, width: Option<usize>) -> Command {
+    let resolved = resolve(opts, parent_dir(path));
+    let mut command = Command::new(&resolved.program);
+    command.arg("--parser=markdown");
+    if let Some(stdin_filepath) = stdin_filepath(path) {
+        command.arg("--stdin-filepath");
+        command.arg(stdin_filepath);
+    }
+    if let Some(width) = width {
+        command.arg("--prose-wrap=always");
+        command.arg(format!("--print-width={width}"));
+    }
+    command.args(&opts.args);
+    command
+}
+
+/// Returns the output of `program` run with `option` and `path`
+pub fn output(program: &Path, option: &str, path: &Path) -> Result<String> {
+    let output = Command::new(program).arg(option).arg(path).output_wc()?;
+    ensure!(
+        output.status.success(),
+        "`{} {option}` exited {}",
+        program.display(),
+        exit_status_adverbial(output.status)
+    );
+    Ok(String::from_utf8(output.stdout)?.trim_end().to_owned())
+}
+
+/// Returns the configuration file that `program` would use for the file at `path`
+///
+/// `None` is returned if no configuration file is found, or if `program` fails.
+pub fn find_config_path(program: &Path, path: &Path) -> Option<String> {
+    // smoelius: `prettier --find-config-path` fails if no configuration file is found.
+    output(program, "--find-config-path", path)
+        .ok()
+        .filter(|config_path| !config_path.is_empty())
+}
+
+/// Returns an error if `version`, the version of the `prettier` at `program`, does not satisfy the
+/// configuration file's requirement
+pub fn check_version(opts: &Options, program: &Path, version: &str) -> Result<()> {
+    let PrettierVersion::Required(requirement) = &opts.prettier_version else {
+        return Ok(());
+    };
+    let parsed = Version::parse(version)
+        .with_context(|| format!("failed to parse `prettier` version `{version}`"))?;
+    ensure!(
+        requirement.matches(&parsed),
+        "`{}` is prettier {version}, but the configuration file requires prettier \
+         `{requirement}`; install a matching version, or pass `--ignore-prettier-version` to use \
+         this one anyway",
+        program.display()
+    );
+    Ok(())
+}
+
+/// Returns the version of the `prettier` at `program`
+///
+/// Each program is run at most once.
+fn version(program: &Path) -> Result<String> {
+    let mut versions = VERSIONS.lock().unwrap();
+    let version = match versions.entry(program.to_path_buf()) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => entry.insert(program_version(program)?),
+    };
+    Ok(version.clone())
+}
+
+/// Returns the path `prettier` should treat the markdown from the file at `path` as coming from
+///
+/// The path is in the same directory as the source file so that `prettier` resolves configuration
+/// files, `overrides`, and `.prettierignore` files as it would for a markdown file beside the
+/// source file. No path is returned for standard input.
+pub fn stdin_filepath(path: &Path) -> Option<PathBuf> {
+    if path == Path::new("<stdin>") {
+        return None;
+    }
+    let mut file_name = path.file_name_wc().ok()?.to_owned();
+    file_name.push(".rustdoc.md");
+    Some(path.with_file_name(file_name))
+}
+
+/// A `prettier` program, and how it was found
+pub struct Resolved {
+    pub program: PathBuf,
+    pub source: Source,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Source {
+    Option,
+    EnvironmentVariable,
+    NodeModules,
+    Path,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Option => write!(f, "`--prettier-path`"),
+            Self::EnvironmentVariable => write!(f, "the `PRETTIER` environment variable"),
+            Self::NodeModules => write!(f, "`node_modules/.bin`"),
+            Self::Path => write!(f, "`PATH`"),
+        }
+    }
+}
+
+/// Returns the `prettier` with which to format files in `dir`
+///
+/// The `prettier` is, in order of preference: the one given by `--prettier-path`, the one given by
+/// the `PRETTIER` environment variable, the one in the `node_modules/.bin` directory nearest
+/// `dir`, or the one in `PATH`.
+pub fn resolve(opts: &Options, dir: &Path) -> Resolved {
+    if let Some(program) = &opts.prettier_path {
+        return Resolved {
+            program: program.clone(),
+            source: Source::Option,
+        };
+    }
+
+    if let Some(program) = var_os_wc("PRETTIER")
+        .ok()
+        .filter(|program| !program.is_empty())
+    {
+        return Resolved {
+            program: PathBuf::from(program),
+            source: Source::EnvironmentVariable,
+        };
+    }
+
+    // smoelius: If `dir` cannot be canonicalized, e.g., because it does not exist,
+    // `node_modules` directories are not searched.
+    if let Ok(Some(program)) = find_nearest(dir, &[Path::new("node_modules/.bin").join(PRETTIER)]) {
+        return Resolved {
+            program,
+            source: Source::NodeModules,
+        };
+    }
+
+    // smoelius: Search `PATH` so that the program's full path can be reported. If the program is
+    // not found, its bare name is used, and running it fails with a "not found" error.
+    let program = var_os_wc("PATH")
+        .ok()
+        .and_then(|path| {
+            env::split_paths(&path)
+                .map(|dir| dir.join(PRETTIER))
+                .find(|program| program.is_file())
+        })
+        .unwrap_or_else(|| PathBuf::from(PRETTIER));
+    Resolved {
+        program,
+        source: Source::Path,
+    }
+}
@@ -0,0 +1,320 @@
+//! # rustfmt-prettier
+//!
+//! Run `rustfmt`, and then format `//!` and `///` comments with rustdoc-prettier
+
+use anyhow::{Result, bail, ensure};
+use elaborate::std::{
+    env::current_exe_wc,
+    fs::read_to_string_wc,
+    io::{ReadContext, WriteContext},
+    path::PathContext,
+    process::{CommandContext, ExitStatusContext},
+};
+use glob::Pattern;
+use std::{
+    env, io,
+    path::{Path, PathBuf},
+    process::{Command, ExitCode, ExitStatus},
+};
+
+#[path = "../pipe.rs"]
+mod pipe;
+use pipe::output_with_stdin;
+
+const RUSTFMT: &str = "rustfmt";
+
+#[rustfmt::skip]
+const HELP: &str = "\
+Usage: rustfmt-prettier [RUSTFMT ARGS]
+
+Runs `rustfmt` with the given arguments, and then runs
+rustdoc-prettier on the files that `rustfmt` formatted. Thus,
+rustfmt-prettier can be used wherever `rustfmt` can. Examples:
+
+    RUSTFMT=rustfmt-prettier cargo fmt
+
+    \"rust-analyzer.rustfmt.overrideCommand\": [\"rustfmt-prettier\"]
+
+If no source files are given, a source file is read from
+standard input, and the result is written to standard output.
+
+The following `rustfmt` options are supported: `--check`,
+`--edition`, and `--emit` with a value of `files` or `stdout`.
+All other arguments are forwarded to `rustfmt` unchanged.
+";
+
+#[derive(Default)]
+struct Options {
+    /// Arguments to pass to `rustfmt`
+    args: Vec<String>,
+    /// Source files given on the command line
+    files: Vec<PathBuf>,
+    /// Whether `args` includes `--check`
+    check: bool,
+    /// Whether formatted files should be written to standard output
+    emit_stdout: bool,
+}
+
+fn main() -> Result<ExitCode> {
+    let Some(opts) = process_args()? else {
+        return Ok(ExitCode::SUCCESS);
+    };
+
+    if opts.files.is_empty() {
+        format_stdin(&opts)
+    } else if opts.emit_stdout {
+        format_files_to_stdout(&opts)
+    } else {
+        format_files(&opts)
+    }
+}
+
+fn process_args() -> Result<Option<Options>> {
+    let mut opts = Options::default();
+    let mut iter = env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--help" || arg == "-h" {
+            println!("{HELP}");
+            return Ok(None);
+        } else if arg == "--version" || arg == "-V" {
+            println!("rustfmt-prettier {}", env!("CARGO_PKG_VERSION"));
+            let status = Command::new(RUSTFMT).arg("--version").status_wc()?;
+            ensure!(status.success(), "`{RUSTFMT} --version` failed");
+            return Ok(None);
+        } else if arg == "--check" {
+            opts.check = true;
+            opts.args.push(arg);
+        } else if arg == "--edition" {
+            let Some(edition) = iter.next() else {
+                bail!("missing argument to --edition");
+            };
+            opts.args.extend([arg, edition]);
+        } else if arg == "--emit" {
+            let Some(emit) = iter.next() else {
+                bail!("missing argument to --emit");
+            };
+            opts.emit_stdout = emit_stdout(&emit)?;
+            opts.args.extend([arg, emit]);
+        } else if let Some(emit) = arg.strip_prefix("--emit=") {
+            opts.emit_stdout = emit_stdout(emit)?;
+            opts.args.push(arg);
+        } else if arg.to_lowercase().ends_with(".rs") {
+            opts.files.push(PathBuf::from(&arg));
+            opts.args.push(arg);
+        } else {
+            opts.args.push(arg);
+        }
+    }
+    Ok(Some(opts))
+}
+
+fn emit_stdout(emit: &str) -> Result<bool> {
+    match emit {
+        "files" => Ok(false),
+        "stdout" => Ok(true),
+        _ => bail!("unsupported `--emit` value: {emit}"),
+    }
+}
+
+fn format_stdin(opts: &Options) -> Result<ExitCode> {
+    let mut contents = String::new();
+    io::stdin().read_to_string_wc(&mut contents)?;
+
+    let Some(formatted) = format_source(opts, &contents)? else {
+        return Ok(ExitCode::FAILURE);
+    };
+
+    if opts.check {
+        if let Some(line) = first_differing_line(&contents, &formatted) {
+            println!("Diff in <stdin> at line {line}");
+            return Ok(ExitCode::FAILURE);
+        }
+    } else {
+        io::stdout().write_all_wc(formatted.as_bytes())?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn format_files_to_stdout(opts: &Options) -> Result<ExitCode> {
+    let paths = module_files(&opts.files)?;
+
+    // smoelius: Each file is passed to `rustfmt` on standard input, so the file arguments are
+    // removed.
+    let rustfmt_args = opts
+        .args
+        .iter()
+        .filter(|arg| {
+            !opts
+                .files
+                .iter()
+                .any(|file| file.as_os_str() == arg.as_str())
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    let opts = Options {
+        args: rustfmt_args,
+        files: Vec::new(),
+        check: opts.check,
+        emit_stdout: true,
+    };
+
+    for path in paths {
+        let contents = read_to_string_wc(&path)?;
+        let Some(formatted) = format_source(&opts, &contents)? else {
+            return Ok(ExitCode::FAILURE);
+        };
+        io::stdout().write_all_wc(formatted.as_bytes())?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn format_files(opts: &Options) -> Result<ExitCode> {
+    let rustfmt_status = Command::new(RUSTFMT).args(&opts.args).status_wc()?;
+
+    // smoelius: If `rustfmt` failed for a reason other than a check failure (e.g., a syntax error),
+    // there is no point in running rustdoc-prettier.
+    if !rustfmt_status.success() && !opts.check {
+        return Ok(exit_code(rustfmt_status));
+    }
+
+    let mut command = Command::new(rustdoc_prettier()?);
+    for path in module_files(&opts.files)? {
+        command.arg(Pattern::escape(&path.to_string_lossy()));
+    }
+    if opts.check {
+        command.arg("--check");
+    }
+    let rustdoc_prettier_status = command.status_wc()?;
+
+    if rustfmt_status.success() {
+        Ok(exit_code(rustdoc_prettier_status))
+    } else {
+        Ok(exit_code(rustfmt_status))
+    }
+}
+
+/// Runs `rustfmt` and then rustdoc-prettier on `contents`
+///
+/// Returns `None` if either program fails. In that case, the program will have written an error
+/// message to standard error.
+fn format_source(opts: &Options, contents: &str) -> Result<Option<String>> {
+    // smoelius: `--check` is removed so that `rustfmt` writes the formatted source to standard
+    // output. The check is then performed by `format_stdin`.
+    let mut command = Command::new(RUSTFMT);
+    command.args(opts.args.iter().filter(|arg| *arg != "--check"));
+    let Some(formatted) = pipe(command, contents)? else {
+        return Ok(None);
+    };
+
+    let mut command = Command::new(rustdoc_prettier()?);
+    command.arg("-");
+    pipe(command, &formatted)
+}
+
+/// Runs `command` with `input` as its standard input, and returns its standard output if it exits
+/// successfully
+fn pipe(mut command: Command, input: &str) -> Result<Option<String>> {
+    let output = output_with_stdin(&mut command, input)?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    String::from_utf8(output.stdout)
+        .map(Some)
+        .map_err(Into::into)
+}
+
+/// Returns the path of the `rustdoc-prettier` binary installed alongside this one
+fn rustdoc_prettier() -> Result<PathBuf> {
+    let current_exe = current_exe_wc()?;
+    Ok(current_exe.with_file_name(format!("rustdoc-prettier{}", env::consts::EXE_SUFFIX)))
+}
+
+/// Returns `roots` together with the files of the out-of-line modules they declare, recursively
+///
+/// Like `rustfmt`, this function treats each root as a crate root. Modules declared with `#[path]`
+/// attributes and modules nested within inline modules are not found.
+fn module_files(roots: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut stack = roots
+        .iter()
+        .map(|root| (root.clone(), true))
+        .collect::<Vec<_>>();
+    while let Some((path, is_root)) = stack.pop() {
+        if files.contains(&path) {
+            continue;
+        }
+        let contents = read_to_string_wc(&path)?;
+        let dir = module_dir(&path, is_root)?;
+        for name in contents.lines().filter_map(declared_module) {
+            for candidate in [
+                dir.join(format!("{name}.rs")),
+                dir.join(name).join("mod.rs"),
+            ] {
+                if candidate.is_file() {
+                    stack.push((candidate, false));
+                    break;
+                }
+            }
+        }
+        files.push(path);
+    }
+    Ok(files)
+}
+
+/// Returns the directory in which the modules declared in `path` reside
+fn module_dir(path: &Path, is_root: bool) -> Result<PathBuf> {
+    let parent = path.parent_wc()?;
+    let stem = path.file_stem_wc()?;
+    if is_root || stem == "mod" {
+        Ok(parent.to_path_buf())
+    } else {
+        Ok(parent.join(stem))
+    }
+}
+
+/// Returns the name of the module declared by `line`, if `line` is an out-of-line module
+/// declaration, e.g., `pub(crate) mod foo;`
+fn declared_module(line: &str) -> Option<&str> {
+    let mut rest = line.trim();
+    if let Some(suffix) = rest.strip_prefix("pub") {
+        rest = suffix.trim_start();
+        if let Some(suffix) = rest.strip_prefix('(') {
+            rest = &suffix[suffix.find(')')? + 1..];
+        }
+    }
+    let name = rest
+        .trim_start()
+        .strip_prefix("mod ")?
+        .trim()
+        .strip_suffix(';')?
+        .trim();
+    let name = name.strip_prefix("r#").unwrap_or(name);
+    if name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+/// Returns the one-based number of the first line at which `a` and `b` differ
+fn first_differing_line(a: &str, b: &str) -> Option<usize> {
+    if a == b {
+        return None;
+    }
+    let n = a
+        .split_inclusive('\n')
+        .zip(b.split_inclusive('\n'))
+        .take_while(|(x, y)| x == y)
+        .count();
+    Some(n + 1)
+}
+
+fn exit_code(status: ExitStatus) -> ExitCode {
+    status
+        .code_wc()
+        .ok()
+        .and_then(|code| u8::try_from(code).ok())
+        .map_or(ExitCode::FAILURE, ExitCode::from)
+}
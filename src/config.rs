@@ -0,0 +1,61 @@
+//! Read rustdoc-prettier's configuration file
+//!
+//! The configuration file is `rustdoc-prettier.toml` or `.rustdoc-prettier.toml`, in the current
+//! directory or its nearest ancestor that has one.
+
+use super::{backend, find_nearest};
+use anyhow::{Context, Result, bail};
+use elaborate::std::fs::read_to_string_wc;
+use semver::VersionReq;
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE_NAMES: [&str; 2] = [".rustdoc-prettier.toml", "rustdoc-prettier.toml"];
+
+#[derive(Debug, Default)]
+pub struct Config {
+    /// The configuration file, if one was found
+    pub path: Option<PathBuf>,
+    /// The backend with which to format doc comments
+    pub backend: Option<backend::Kind>,
+    /// The versions of `prettier` with which doc comments may be formatted
+    pub prettier: Option<VersionReq>,
+}
+
+/// Reads the configuration file in `dir` or its nearest ancestor
+///
+/// If no configuration file is found, the default configuration is returned.
+pub fn load(dir: &Path) -> Result<Config> {
+    let Some(path) = find_nearest(dir, &CONFIG_FILE_NAMES)? else {
+        return Ok(Config::default());
+    };
+    let mut config = parse(&read_to_string_wc(&path)?)
+        .with_context(|| format!("failed to parse `{}`", path.display()))?;
+    config.path = Some(path);
+    Ok(config)
+}
+
+fn parse(contents: &str) -> Result<Config> {
+    let table = contents.parse::<toml::Table>()?;
+    let mut config = Config::default();
+    for (key, value) in table {
+        match key.as_str() {
+            "backend" => {
+                let Some(backend) = value.as_str() else {
+                    bail!("`backend` is not a string");
+                };
+                config.backend = Some(backend::Kind::parse(backend)?);
+            }
+            "prettier" => {
+                let Some(prettier) = value.as_str() else {
+                    bail!("`prettier` is not a string");
+                };
+                let requirement = VersionReq::parse(prettier).with_context(|| {
+                    format!("failed to parse `prettier` version requirement `{prettier}`")
+                })?;
+                config.prettier = Some(requirement);
+            }
+            _ => bail!("unknown key `{key}`"),
+        }
+    }
+    Ok(config)
+}
@@ -0,0 +1,274 @@
+//! Report on the environment in which rustdoc-prettier formats the source files at a path
+//!
+//! Each check prints a line. A check that fails also prints advice on how to fix the problem, and
+//! causes `doctor` to return an error once all checks have run.
+
+use super::{
+    Characteristics, DocKind, Options, backend, effective_width, find_rustfmt_toml, parent_dir,
+    program_version, rustfmt_toml_width,
+};
+use anyhow::{Result, ensure};
+use elaborate::std::{env::current_dir_wc, path::PathContext, process::CommandContext};
+use std::{path::Path, process::Command};
+
+/// Indentations at which the widths of doc comments are reported
+const SAMPLE_INDENTS: [usize; 3] = [0, 4, 8];
+
+/// Checks whether `node` can load the module named by its first argument
+///
+/// Relative and absolute paths are loaded from the file system. Other names are resolved as
+/// packages, relative to the current directory.
+const LOAD_PLUGIN: &str = "\
+const path = require('path');
+const url = require('url');
+const name = process.argv[1];
+const specifier = name.startsWith('.') || path.isAbsolute(name)
+    ? url.pathToFileURL(path.resolve(name)).href
+    : name;
+import(specifier).catch((error) => {
+    console.error(error.message);
+    process.exit(1);
+});
+";
+
+fn ok(label: &str, message: impl AsRef<str>) {
+    println!("{label}: {}", message.as_ref());
+}
+
+#[derive(Default)]
+struct Report {
+    problems: usize,
+}
+
+impl Report {
+    fn problem(&mut self, label: &str, message: impl AsRef<str>, advice: impl AsRef<str>) {
+        println!("{label}: {}", message.as_ref());
+        println!("    advice: {}", advice.as_ref());
+        self.problems += 1;
+    }
+}
+
+pub fn doctor(opts: &Options) -> Result<()> {
+    ensure!(
+        opts.patterns.len() <= 1,
+        "`doctor` accepts at most one path"
+    );
+    let path = opts.patterns.first().map_or(Path::new("."), Path::new);
+    ensure!(path.try_exists_wc()?, "`{}` does not exist", path.display());
+
+    // smoelius: If `path` is a directory, the checks are performed for a hypothetical source file
+    // in that directory.
+    let (dir, file) = if path.is_dir() {
+        (path, path.join("lib.rs"))
+    } else {
+        (parent_dir(path), path.to_path_buf())
+    };
+
+    let mut report = Report::default();
+
+    check_widths(opts, &mut report)?;
+
+    let backend = opts.backend();
+    ok("backend", backend.name());
+    if opts.backend.unwrap_or_default() == backend::Kind::Prettier {
+        check_prettier(opts, dir, &file, &mut report);
+    } else if let Err(error) = backend.check(opts, &[&file]) {
+        report.problem(
+            "backend",
+            format!("`{}` cannot be used: {error:#}", backend.name()),
+            "install the program the backend runs, or select another backend with `--backend`",
+        );
+    }
+
+    let problems = report.problems;
+    ensure!(
+        problems == 0,
+        "found {problems} problem{}",
+        if problems == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+fn check_widths(opts: &Options, report: &mut Report) -> Result<()> {
+    // smoelius: Like `rustdoc-prettier` itself, `doctor` looks for `rustfmt.toml` relative to the
+    // current directory.
+    let max_width = match find_rustfmt_toml(&current_dir_wc()?) {
+        Ok(Some(path)) => match rustfmt_toml_width(&path) {
+            Ok(width) => {
+                ok("rustfmt.toml", path.display().to_string());
+                width.map(|width| width.max_width)
+            }
+            Err(error) => {
+                report.problem(
+                    "rustfmt.toml",
+                    format!("`{}` is invalid: {error:#}", path.display()),
+                    "make `max_width` and `comment_width`, if present, nonnegative integers",
+                );
+                None
+            }
+        },
+        Ok(None) => {
+            ok("rustfmt.toml", "none found");
+            None
+        }
+        Err(error) => {
+            report.problem(
+                "rustfmt.toml",
+                format!("{error:#}"),
+                "check that the path exists and that its directories are readable",
+            );
+            None
+        }
+    };
+
+    let Some(max_width) = opts.max_width.or(max_width) else {
+        ok(
+            "width",
+            "no maximum width applies; `prettier`'s configuration determines how prose is wrapped",
+        );
+        return Ok(());
+    };
+    ok("max width", max_width.to_string());
+    for kind in [DocKind::Inner, DocKind::Outer] {
+        let widths = SAMPLE_INDENTS
+            .iter()
+            .map(|&indent| {
+                let width = effective_width(max_width, Characteristics { indent, kind });
+                format!("{width} at indent {indent}")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        ok(&format!("`{}` width", kind.syntax()), widths);
+    }
+    Ok(())
+}
+
+fn check_prettier(opts: &Options, dir: &Path, file: &Path, report: &mut Report) {
+    let resolved = backend::resolve_prettier(opts, dir);
+    let program = &resolved.program;
+    let version = match program_version(program) {
+        Ok(version) => version,
+        Err(error) => {
+            report.problem(
+                "prettier",
+                format!(
+                    "`{}` (found via {}) failed to run: {error:#}",
+                    program.display(),
+                    resolved.source
+                ),
+                "install prettier, e.g., with `npm install --save-dev prettier`, or give the path \
+                 of a `prettier` program with `--prettier-path` or the `PRETTIER` environment \
+                 variable",
+            );
+            return;
+        }
+    };
+    ok(
+        "prettier",
+        format!(
+            "{} (found via {}), version {version}",
+            program.display(),
+            resolved.source
+        ),
+    );
+    if let Err(error) = backend::check_prettier_version(opts, program, &version) {
+        report.problem(
+            "prettier version",
+            format!("{error:#}"),
+            "install a version of prettier that satisfies the `prettier` requirement in \
+             `rustdoc-prettier.toml`, or change the requirement",
+        );
+    }
+
+    let stdin_filepath = backend::stdin_filepath(file).unwrap_or_else(|| file.to_path_buf());
+    check_prettier_config(program, &stdin_filepath, report);
+
+    check_plugins(opts, report);
+}
+
+fn check_prettier_config(program: &Path, stdin_filepath: &Path, report: &mut Report) {
+    let config_path = backend::find_prettier_config_path(program, stdin_filepath);
+    ok(
+        "prettier config",
+        config_path.as_deref().unwrap_or("none found"),
+    );
+
+    match backend::prettier_output(program, "--file-info", stdin_filepath).and_then(|file_info| {
+        serde_json::from_str::<serde_json::Value>(&file_info).map_err(Into::into)
+    }) {
+        Ok(file_info) if file_info["ignored"] == true => ok(
+            "prettier ignore",
+            format!(
+                "`{}` is ignored, so doc comments in the file are left unformatted",
+                stdin_filepath.display()
+            ),
+        ),
+        Ok(_) => ok("prettier ignore", "not ignored"),
+        Err(error) => report.problem(
+            "prettier ignore",
+            format!("failed to get file info: {error:#}"),
+            "upgrade prettier to a version that supports `--file-info`",
+        ),
+    }
+}
+
+fn check_plugins(opts: &Options, report: &mut Report) {
+    let plugins = plugins(&opts.args);
+    if plugins.is_empty() {
+        return;
+    }
+
+    if let Err(error) = program_version("node") {
+        report.problem(
+            "node",
+            format!("`node` failed to run: {error:#}"),
+            "install Node.js so that the plugins can be checked",
+        );
+        return;
+    }
+
+    for plugin in plugins {
+        let label = format!("plugin `{plugin}`");
+        let output = Command::new("node")
+            .args(["--eval", LOAD_PLUGIN])
+            .arg(plugin)
+            .output_wc();
+        match output {
+            Ok(output) if output.status.success() => ok(&label, "loads"),
+            Ok(output) => report.problem(
+                &label,
+                format!(
+                    "failed to load: {}",
+                    String::from_utf8_lossy(&output.stderr).trim_end()
+                ),
+                if plugin.starts_with('.') || Path::new(plugin).is_absolute() {
+                    String::from("check that the plugin's path is correct")
+                } else {
+                    format!(
+                        "install the plugin, e.g., with `npm install --save-dev {plugin}`, in the \
+                         current directory or an ancestor"
+                    )
+                },
+            ),
+            Err(error) => report.problem(
+                &label,
+                format!("failed to run `node`: {error:#}"),
+                "install Node.js",
+            ),
+        }
+    }
+}
+
+/// Returns the plugins named by `--plugin` options in `args`
+fn plugins(args: &[String]) -> Vec<&str> {
+    let mut plugins = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--plugin" {
+            plugins.extend(iter.next().map(String::as_str));
+        } else if let Some(plugin) = arg.strip_prefix("--plugin=") {
+            plugins.push(plugin);
+        }
+    }
+    plugins
+}
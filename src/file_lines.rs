@@ -0,0 +1,106 @@
+//! Limit formatting to given line ranges, like `rustfmt`'s `--file-lines` option
+
+use anyhow::{Result, anyhow, ensure};
+use elaborate::std::path::PathContext;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    ops::{Range, RangeInclusive},
+    path::{Path, PathBuf},
+};
+
+/// Maps files to the one-based, inclusive line ranges that should be formatted in them
+///
+/// Files not in the map are not formatted at all.
+#[derive(Clone, Debug, Default)]
+pub struct FileLines(HashMap<PathBuf, Vec<RangeInclusive<usize>>>);
+
+/// The name `rustfmt` uses to refer to standard input in `--file-lines` arguments
+const STDIN: &str = "stdin";
+
+impl FileLines {
+    /// Parses a JSON list of `{"file": ..., "range": [start, end]}` objects, as accepted by
+    /// `rustfmt`'s `--file-lines` option
+    pub fn parse(s: &str) -> Result<Self> {
+        let value = serde_json::from_str::<Value>(s)?;
+        let entries = value
+            .as_array()
+            .ok_or_else(|| anyhow!("`--file-lines` argument is not a list"))?;
+        let mut file_lines = Self::default();
+        for entry in entries {
+            let file = entry["file"]
+                .as_str()
+                .ok_or_else(|| anyhow!("entry has no `file`: {entry}"))?;
+            let range = entry["range"]
+                .as_array()
+                .and_then(|range| {
+                    let [start, end] = range.as_slice() else {
+                        return None;
+                    };
+                    let start = usize::try_from(start.as_u64()?).ok()?;
+                    let end = usize::try_from(end.as_u64()?).ok()?;
+                    Some(start..=end)
+                })
+                .ok_or_else(|| anyhow!("entry has no valid `range`: {entry}"))?;
+            ensure!(
+                *range.start() <= *range.end(),
+                "entry has an empty `range`: {entry}"
+            );
+            file_lines.insert(Path::new(file), range);
+        }
+        Ok(file_lines)
+    }
+
+    pub fn insert(&mut self, path: &Path, range: RangeInclusive<usize>) {
+        self.0.entry(normalize(path)).or_default().push(range);
+    }
+
+    /// Returns the files with line ranges that should be formatted
+    ///
+    /// The paths of existing files are canonical.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.0.keys().map(PathBuf::as_path)
+    }
+
+    /// Returns true if `lines`, a one-based, half-open range of lines in the file at `path`,
+    /// intersects a range that should be formatted
+    pub fn intersects(&self, path: &Path, lines: &Range<usize>) -> bool {
+        self.0.get(&normalize(path)).is_some_and(|ranges| {
+            ranges
+                .iter()
+                .any(|range| *range.start() < lines.end && lines.start <= *range.end())
+        })
+    }
+}
+
+/// Returns `path` in a form suitable for use as a key in a [`FileLines`] map
+///
+/// Paths of existing files are canonicalized so that, e.g., `./src/lib.rs` and `src/lib.rs` refer
+/// to the same entry. The path `<stdin>`, which rustdoc-prettier uses for standard input, is
+/// mapped to `stdin`.
+fn normalize(path: &Path) -> PathBuf {
+    if path == Path::new("<stdin>") {
+        return PathBuf::from(STDIN);
+    }
+    path.canonicalize_wc()
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intersects() {
+        let file_lines =
+            FileLines::parse(r#"[{"file":"stdin","range":[3,4]},{"file":"stdin","range":[9,9]}]"#)
+                .unwrap();
+        let stdin = Path::new("<stdin>");
+        assert!(!file_lines.intersects(stdin, &(1..3)));
+        assert!(file_lines.intersects(stdin, &(1..4)));
+        assert!(file_lines.intersects(stdin, &(4..6)));
+        assert!(!file_lines.intersects(stdin, &(5..9)));
+        assert!(file_lines.intersects(stdin, &(9..10)));
+        assert!(!file_lines.intersects(Path::new("lib.rs"), &(1..10)));
+    }
+}
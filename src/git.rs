@@ -0,0 +1,136 @@
+//! Interactions with git
+
+use super::{file_lines::FileLines, pipe::output_with_stdin};
+use anyhow::{Context, Result, anyhow, ensure};
+use elaborate::std::process::CommandContext;
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+/// Returns the lines added or modified since `rev` in the current git repository's working tree
+///
+/// Untracked files that are not ignored are considered added in their entirety.
+pub fn changed_lines(rev: &str) -> Result<FileLines> {
+    let toplevel = toplevel()?;
+
+    let mut file_lines = FileLines::default();
+
+    let diff = git(
+        &toplevel,
+        &[
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--unified=0",
+            // smoelius: Set the prefixes explicitly so that they are not affected by the user's
+            // `diff.noprefix` or `diff.mnemonicPrefix` configuration.
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            rev,
+        ],
+    )?;
+    let mut path = None;
+    // smoelius: An added line that starts with `++ ` looks like a `+++ ` header. So a `+++ ` line
+    // is treated as a header only if it immediately follows a `--- ` line outside of a hunk. A
+    // hunk's lines all start with `+`, `-`, ` `, or `\`, so a hunk ends at the next `diff ` line.
+    let mut in_hunk = false;
+    let mut follows_old_file_header = false;
+    for line in diff.lines() {
+        let is_old_file_header = !in_hunk && line.starts_with("--- ");
+        if line.starts_with("diff ") {
+            in_hunk = false;
+            path = None;
+        } else if let Some(suffix) = line.strip_prefix("+++ ")
+            && follows_old_file_header
+        {
+            // smoelius: A file's name is followed by a tab if it contains a space.
+            let suffix = suffix.trim_end_matches('\t');
+            path = suffix.strip_prefix("b/").map(|path| toplevel.join(path));
+        } else if let Some(suffix) = line.strip_prefix("@@ ") {
+            in_hunk = true;
+            let lines = parse_hunk_header(suffix)
+                .ok_or_else(|| anyhow!("failed to parse hunk header: {line}"))?;
+            if let Some(path) = &path
+                && !lines.is_empty()
+            {
+                file_lines.insert(path, lines.start..=lines.end - 1);
+            }
+        }
+        follows_old_file_header = is_old_file_header;
+    }
+
+    let untracked = git(
+        &toplevel,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )?;
+    for path in untracked.split_terminator('\0') {
+        file_lines.insert(&toplevel.join(path), 1..=usize::MAX);
+    }
+
+    Ok(file_lines)
+}
+
+/// Parses the portion of a hunk header following `@@ `, e.g., `-12,3 +14,5 @@`, and returns the
+/// one-based, half-open range of lines the hunk adds to the new file
+///
+/// The range is empty if the hunk only removes lines.
+fn parse_hunk_header(header: &str) -> Option<Range<usize>> {
+    let new = header.split(' ').nth(1)?.strip_prefix('+')?;
+    let (start, count) = new.split_once(',').unwrap_or((new, "1"));
+    let start = start.parse::<usize>().ok()?;
+    let count = count.parse::<usize>().ok()?;
+    Some(start..start + count)
+}
+
+/// Returns the root of the current git repository's working tree
+pub fn toplevel() -> Result<PathBuf> {
+    let stdout = git(Path::new("."), &["rev-parse", "--show-toplevel"])
+        .with_context(|| "failed to find git repository")?;
+    Ok(PathBuf::from(stdout.trim_end()))
+}
+
+/// Runs git in `dir` with `args` and returns its standard output
+pub fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output_wc()?;
+    git_output(args, output)
+}
+
+/// Runs git in `dir` with `args` and `input` as its standard input, and returns its standard
+/// output
+pub fn git_with_stdin(dir: &Path, args: &[&str], input: &str) -> Result<String> {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(dir).args(args).stderr(Stdio::piped());
+    let output = output_with_stdin(&mut command, input)?;
+    git_output(args, output)
+}
+
+fn git_output(args: &[&str], output: Output) -> Result<String> {
+    ensure!(
+        output.status.success(),
+        "`git {}` failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr).trim_end()
+    );
+    String::from_utf8(output.stdout).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hunk_header_handles_counts() {
+        assert_eq!(Some(14..19), parse_hunk_header("-12,3 +14,5 @@"));
+        assert_eq!(Some(7..8), parse_hunk_header("-7 +7 @@ fn foo() {"));
+        assert_eq!(Some(6..6), parse_hunk_header("-7,2 +6,0 @@"));
+        assert_eq!(None, parse_hunk_header("garbage"));
+    }
+}
@@ -0,0 +1,201 @@
+//! Install a git pre-commit hook that runs rustdoc-prettier on the staged source files
+//!
+//! The hook's commands are delimited by marker comments. If the hook already exists, the commands
+//! are appended to it, or the commands between the markers are replaced.
+
+use super::{Input, Options, PrettierVersion, Writes, git};
+use anyhow::{Result, bail, ensure};
+use elaborate::std::{
+    fs::{create_dir_all_wc, read_to_string_wc, write_wc},
+    path::PathContext,
+};
+use std::path::Path;
+
+const BEGIN_MARKER: &str = "# rustdoc-prettier: begin";
+
+const END_MARKER: &str = "# rustdoc-prettier: end";
+
+const SHEBANG: &str = "#!/bin/sh";
+
+pub fn install_hook(opts: &Options) -> Result<()> {
+    ensure!(
+        opts.patterns.is_empty(),
+        "`install-hook` cannot be used together with source files"
+    );
+    // smoelius: The hook runs `rustdoc-prettier --staged`, to which the other options do not
+    // apply. Rejecting them is better than silently dropping them from the hook.
+    ensure!(
+        opts.input == Input::Files
+            && !opts.watch
+            && !opts.interactive
+            && opts.writes == Writes::Transactional
+            && opts.out_dir.is_none()
+            && opts.report.is_none()
+            && opts.file_lines.is_none()
+            && opts.changed_since.is_none(),
+        "`install-hook` accepts only `--max-width`, `--backend`, `--prettier-path`, \
+         `--ignore-prettier-version`, and options to pass to `prettier`"
+    );
+
+    let toplevel = git::toplevel()?;
+    let hook = git::git(&toplevel, &["rev-parse", "--git-path", "hooks/pre-commit"])?;
+    let path = toplevel.join(hook.trim_end());
+
+    let block = format!(
+        "{BEGIN_MARKER}\n{} || exit 1\n{END_MARKER}\n",
+        command(opts)
+    );
+
+    let contents = if path.try_exists_wc()? {
+        let contents = read_to_string_wc(&path)?;
+        chain(&path, &contents, &block)?
+    } else {
+        format!("{SHEBANG}\n\n{block}")
+    };
+
+    create_dir_all_wc(path.parent_wc()?)?;
+    write_wc(&path, contents)?;
+    make_executable(&path)?;
+
+    eprintln!("Installed pre-commit hook: {}", path.display());
+
+    Ok(())
+}
+
+/// Returns the command the hook runs, i.e., rustdoc-prettier with `--staged` and the options in
+/// `opts`
+fn command(opts: &Options) -> String {
+    let mut words = vec![String::from("rustdoc-prettier"), String::from("--staged")];
+    if let Some(max_width) = opts.max_width {
+        words.extend([String::from("--max-width"), max_width.to_string()]);
+    }
+    if let Some(backend) = opts.backend {
+        words.extend([
+            String::from("--backend"),
+            backend.backend().name().to_owned(),
+        ]);
+    }
+    if let Some(prettier_path) = &opts.prettier_path {
+        words.extend([
+            String::from("--prettier-path"),
+            quote(&prettier_path.to_string_lossy()),
+        ]);
+    }
+    if matches!(opts.prettier_version, PrettierVersion::Ignored) {
+        words.push(String::from("--ignore-prettier-version"));
+    }
+    words.extend(opts.args.iter().map(|arg| quote(arg)));
+    words.join(" ")
+}
+
+/// Returns `contents`, an existing hook, with its rustdoc-prettier commands replaced by `block`,
+/// or with `block` appended if the hook has no such commands
+fn chain(path: &Path, contents: &str, block: &str) -> Result<String> {
+    if let Some(begin) = contents.find(BEGIN_MARKER) {
+        let Some(end) = contents[begin..].find(END_MARKER) else {
+            bail!("`{}` has no `{END_MARKER}` line", path.display());
+        };
+        let end = begin + end + END_MARKER.len();
+        let end = contents[end..].strip_prefix('\n').map_or(end, |_| end + 1);
+        return Ok(format!("{}{block}{}", &contents[..begin], &contents[end..]));
+    }
+
+    // smoelius: The commands are written in shell syntax, so they can be appended only to a shell
+    // script.
+    let first_line = contents.lines().next().unwrap_or_default();
+    ensure!(
+        invokes_sh(first_line),
+        "`{}` exists but is not a shell script; add the following to it manually:\n\n{block}",
+        path.display()
+    );
+
+    // smoelius: Commands after a trailing `exit` or `exec` would never run. So `block` is inserted
+    // before such a command rather than appended.
+    if let Some(start) = trailing_exit(contents) {
+        let (before, after) = contents.split_at(start);
+        let separator = if before.ends_with("\n\n") { "" } else { "\n" };
+        return Ok(format!("{before}{separator}{block}\n{after}"));
+    }
+
+    let separator = if contents.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    Ok(format!("{contents}{separator}{block}"))
+}
+
+/// Returns whether `line` is a shebang line whose interpreter is a POSIX-compatible shell
+///
+/// Examples of such lines are `#!/bin/sh -e` and `#!/usr/bin/env bash`.
+fn invokes_sh(line: &str) -> bool {
+    let Some(command) = line.strip_prefix("#!") else {
+        return false;
+    };
+    let mut words = command.split_whitespace();
+    let Some(mut interpreter) = words.next() else {
+        return false;
+    };
+    if file_name(interpreter) == "env" {
+        // smoelius: Skip `env`'s options, e.g., `-S`.
+        let Some(word) = words.find(|word| !word.starts_with('-')) else {
+            return false;
+        };
+        interpreter = word;
+    }
+    matches!(
+        file_name(interpreter),
+        "sh" | "bash" | "dash" | "ksh" | "zsh"
+    )
+}
+
+/// Returns the last component of `path`, a path in a shebang line
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Returns the offset of the hook's last command if that command is `exit` or `exec`
+///
+/// Blank lines and comments are ignored. The shebang line is never considered a command.
+fn trailing_exit(contents: &str) -> Option<usize> {
+    let mut offset = 0;
+    let mut last_command = None;
+    for (i, line) in contents.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        if i != 0 && !trimmed.is_empty() && !trimmed.starts_with('#') {
+            last_command = Some((offset, trimmed));
+        }
+        offset += line.len();
+    }
+    let (start, command) = last_command?;
+    let name = command.split_whitespace().next()?;
+    (name == "exit" || name == "exec").then_some(start)
+}
+
+/// Quotes `arg` for use in a shell script, if necessary
+fn quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_=.,/:@+".contains(c))
+    {
+        return arg.to_owned();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use elaborate::std::fs::{metadata_wc, set_permissions_wc};
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = metadata_wc(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o111);
+    set_permissions_wc(path, permissions)
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
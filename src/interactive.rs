@@ -0,0 +1,160 @@
+//! Ask the user whether to accept each reformatted doc comment
+//!
+//! Answers are read from standard input one line at a time, so they can be piped in.
+
+use super::FormattedChunk;
+use anyhow::{Context, Result, bail, ensure};
+use elaborate::std::{
+    env::var_wc,
+    fs::read_to_string_wc,
+    io::{BufReadContext, WriteContext},
+    process::CommandContext,
+};
+use std::{
+    io,
+    path::Path,
+    process::Command,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use tempfile::Builder;
+
+/// Number of lines of context to show before and after a chunk
+const CONTEXT: usize = 2;
+
+const HELP: &str = "\
+y - accept this change
+n - reject this change
+e - edit the formatted doc comment, and accept the result
+a - accept this change and all remaining changes in this file
+q - quit; reject this change and all remaining changes
+? - print help";
+
+/// Set when the user quits, after which all remaining changes are rejected
+static QUIT: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Answer {
+    Accept,
+    Reject,
+    Edit,
+    AcceptAll,
+    Quit,
+}
+
+/// Shows each chunk in `formatted_chunks` that changed, and returns the chunks that the user
+/// accepted
+///
+/// `contents` are the contents of the file at `path` from which the chunks were read.
+pub fn review(
+    path: &Path,
+    contents: &str,
+    formatted_chunks: Vec<FormattedChunk>,
+) -> Result<Vec<FormattedChunk>> {
+    let lines = contents.lines().collect::<Vec<_>>();
+    let mut accept_all = false;
+    let mut accepted = Vec::new();
+    for mut formatted_chunk in formatted_chunks {
+        if !formatted_chunk.is_changed() || QUIT.load(Ordering::SeqCst) {
+            continue;
+        }
+        if accept_all {
+            accepted.push(formatted_chunk);
+            continue;
+        }
+        show(path, &lines, &formatted_chunk);
+        match prompt()? {
+            Answer::Accept => accepted.push(formatted_chunk),
+            Answer::Reject => {}
+            Answer::Edit => {
+                formatted_chunk.docs = edit(&formatted_chunk.docs)?;
+                accepted.push(formatted_chunk);
+            }
+            Answer::AcceptAll => {
+                accept_all = true;
+                accepted.push(formatted_chunk);
+            }
+            Answer::Quit => QUIT.store(true, Ordering::SeqCst),
+        }
+    }
+    Ok(accepted)
+}
+
+/// Writes a diff of `formatted_chunk` with a few lines of context to standard error
+fn show(path: &Path, lines: &[&str], formatted_chunk: &FormattedChunk) {
+    let chunk_lines = &formatted_chunk.chunk.lines;
+    // smoelius: `chunk_lines` is one-based, whereas `lines` is indexed from zero.
+    let before = chunk_lines.start.saturating_sub(1 + CONTEXT)..chunk_lines.start - 1;
+    let after = chunk_lines.end - 1..(chunk_lines.end - 1 + CONTEXT).min(lines.len());
+    eprintln!(
+        "{}:{}-{}",
+        path.display(),
+        chunk_lines.start,
+        chunk_lines.end - 1
+    );
+    for line in &lines[before] {
+        eprintln!(" {line}");
+    }
+    for line in formatted_chunk.chunk.source.lines() {
+        eprintln!("-{line}");
+    }
+    for line in formatted_chunk.docs.lines() {
+        eprintln!("+{line}");
+    }
+    for line in &lines[after] {
+        eprintln!(" {line}");
+    }
+}
+
+fn prompt() -> Result<Answer> {
+    let mut stdin = io::stdin().lock();
+    loop {
+        eprint!("Accept this change [y,n,e,a,q,?]? ");
+        io::stderr().flush_wc()?;
+        let mut line = String::new();
+        // smoelius: If standard input is exhausted, treat it as though the user quit.
+        if stdin.read_line_wc(&mut line)? == 0 {
+            eprintln!();
+            return Ok(Answer::Quit);
+        }
+        match line.trim() {
+            "y" => return Ok(Answer::Accept),
+            "n" => return Ok(Answer::Reject),
+            "e" => return Ok(Answer::Edit),
+            "a" => return Ok(Answer::AcceptAll),
+            "q" => return Ok(Answer::Quit),
+            _ => eprintln!("{HELP}"),
+        }
+    }
+}
+
+/// Opens `docs` in the user's editor, and returns the edited text
+///
+/// The editor is determined by the `VISUAL` or `EDITOR` environment variable, in that order.
+fn edit(docs: &str) -> Result<String> {
+    let editor = var_wc("VISUAL").or_else(|_| var_wc("EDITOR"))?;
+    let mut words = editor.split_whitespace();
+    let Some(program) = words.next() else {
+        bail!("editor command is empty");
+    };
+
+    // smoelius: The file is created with a random name and without following symbolic links, so
+    // another user cannot cause the docs to be written elsewhere. The file is removed when
+    // `tempfile` is dropped.
+    let mut tempfile = Builder::new()
+        .prefix("rustdoc-prettier-")
+        .suffix(".rs")
+        .tempfile()
+        .with_context(|| "failed to create temporary file")?;
+    tempfile.write_all_wc(docs.as_bytes())?;
+    tempfile.flush_wc()?;
+    let path = tempfile.path();
+    let status = Command::new(program).args(words).arg(path).status_wc();
+    let edited = read_to_string_wc(path);
+    ensure!(status?.success(), "`{editor}` failed");
+
+    let mut edited = edited?;
+    if !edited.ends_with('\n') {
+        edited.push('\n');
+    }
+    Ok(edited)
+}
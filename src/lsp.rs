@@ -0,0 +1,373 @@
+//! A Language Server Protocol server that formats doc comments
+//!
+//! The server communicates over standard input and output, and supports:
+//!
+//! - `textDocument/rangeFormatting`, limited to the doc comments in the range
+//! - a "Reformat this doc comment" code action for the chunk under the cursor
+//! - diagnostics for chunks that differ from `prettier`'s output, computed when a document is
+//!   opened or saved
+//!
+//! Only full document synchronization is supported.
+
+use super::{
+    Chunk, FormattedChunk, Options, check_backend, chunk, format_chunks, parent_dir,
+    rustfmt_max_width, with_line_endings,
+};
+use anyhow::{Result, anyhow};
+use elaborate::std::io::{BufReadContext, ReadContext, WriteContext};
+use serde_json::{Value, json};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    ops::{Range, RangeInclusive},
+    path::{Path, PathBuf},
+};
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const REQUEST_FAILED: i64 = -32803;
+
+/// `TextDocumentSyncKind.Full`, i.e., documents are synced by always sending their full content
+const TEXT_DOCUMENT_SYNC_KIND_FULL: i64 = 1;
+
+const DIAGNOSTIC_SEVERITY_WARNING: i64 = 2;
+
+const REFORMAT_THIS_DOC_COMMENT: &str = "Reformat this doc comment";
+
+struct Server {
+    opts: Options,
+    /// Maps the URI of each open document to its text
+    documents: HashMap<String, String>,
+}
+
+pub fn serve(mut opts: Options) -> Result<()> {
+    // smoelius: The server always needs `prettier`'s output, so a `--check` option is not
+    // forwarded.
+    opts.args.retain(|arg| arg != "--check");
+
+    let mut server = Server {
+        opts,
+        documents: HashMap::new(),
+    };
+
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+
+    while let Some(message) = read_message(&mut stdin)? {
+        let method = message.get("method").and_then(Value::as_str);
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        match (method, message.get("id")) {
+            (Some("exit"), _) => break,
+            (Some(method), Some(id)) => {
+                let response = match server.handle_request(method, &params) {
+                    Ok(Some(result)) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Ok(None) => {
+                        error_response(id, METHOD_NOT_FOUND, &format!("unknown method: {method}"))
+                    }
+                    Err(error) => error_response(id, REQUEST_FAILED, &format!("{error:?}")),
+                };
+                write_message(&mut stdout, &response)?;
+            }
+            (Some(method), None) => {
+                for notification in server.handle_notification(method, &params) {
+                    write_message(&mut stdout, &notification)?;
+                }
+            }
+            // smoelius: A response to a request from the server. The server sends no requests, so
+            // there is nothing to do.
+            (None, _) => {}
+        }
+    }
+
+    Ok(())
+}
+
+impl Server {
+    /// Returns `Ok(None)` if `method` is not supported
+    fn handle_request(&mut self, method: &str, params: &Value) -> Result<Option<Value>> {
+        match method {
+            "initialize" => Ok(Some(json!({
+                "capabilities": {
+                    "textDocumentSync": {
+                        "openClose": true,
+                        "change": TEXT_DOCUMENT_SYNC_KIND_FULL,
+                        "save": { "includeText": false },
+                    },
+                    "documentRangeFormattingProvider": true,
+                    "codeActionProvider": {
+                        "codeActionKinds": ["quickfix"],
+                    },
+                },
+                "serverInfo": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            }))),
+            "shutdown" => Ok(Some(Value::Null)),
+            "textDocument/rangeFormatting" => self.range_formatting(params).map(Some),
+            "textDocument/codeAction" => self.code_action(params).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the notifications to send in response to the notification
+    fn handle_notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let Some(uri) = params["textDocument"]["uri"].as_str() else {
+            return Vec::new();
+        };
+        match method {
+            "textDocument/didOpen" => {
+                if let Some(text) = params["textDocument"]["text"].as_str() {
+                    self.documents.insert(uri.to_owned(), text.to_owned());
+                }
+            }
+            // smoelius: Computing diagnostics runs `prettier`, which is too slow to do on every
+            // change. So a change only updates the document's text, and diagnostics are
+            // recomputed when the document is saved.
+            "textDocument/didChange" => {
+                if let Some(text) = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                {
+                    self.documents.insert(uri.to_owned(), text.to_owned());
+                }
+                return Vec::new();
+            }
+            "textDocument/didSave" => {}
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                return vec![publish_diagnostics(uri, &[])];
+            }
+            _ => return Vec::new(),
+        }
+        if !self.documents.contains_key(uri) {
+            return Vec::new();
+        }
+        match self.format(uri, |_| true) {
+            Ok(formatted_chunks) => vec![publish_diagnostics(uri, &formatted_chunks)],
+            Err(error) => {
+                eprintln!("Warning: failed to compute diagnostics for {uri}: {error:?}");
+                Vec::new()
+            }
+        }
+    }
+
+    fn range_formatting(&self, params: &Value) -> Result<Value> {
+        let uri = text_document_uri(params)?;
+        let range = line_range(&params["range"])?;
+        let text = self.text(uri)?;
+        let formatted_chunks = self.format(uri, |chunk| {
+            let lines = zero_based(&chunk.lines);
+            lines.start <= *range.end() && *range.start() < lines.end
+        })?;
+        let text_edits = formatted_chunks
+            .iter()
+            .filter(|formatted_chunk| formatted_chunk.is_changed())
+            .map(|formatted_chunk| text_edit(text, formatted_chunk))
+            .collect::<Vec<_>>();
+        Ok(Value::Array(text_edits))
+    }
+
+    fn code_action(&self, params: &Value) -> Result<Value> {
+        let uri = text_document_uri(params)?;
+        let range = line_range(&params["range"])?;
+        let line = *range.start();
+        let text = self.text(uri)?;
+        let formatted_chunks =
+            self.format(uri, |chunk| zero_based(&chunk.lines).contains(&line))?;
+        let code_actions = formatted_chunks
+            .iter()
+            .filter(|formatted_chunk| formatted_chunk.is_changed())
+            .map(|formatted_chunk| {
+                json!({
+                    "title": REFORMAT_THIS_DOC_COMMENT,
+                    "kind": "quickfix",
+                    "diagnostics": [diagnostic(formatted_chunk)],
+                    "edit": {
+                        "changes": {
+                            uri: [text_edit(text, formatted_chunk)],
+                        },
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        Ok(Value::Array(code_actions))
+    }
+
+    /// Formats the chunks of the document at `uri` for which `predicate` returns true
+    fn format(&self, uri: &str, predicate: impl Fn(&Chunk) -> bool) -> Result<Vec<FormattedChunk>> {
+        let text = self.text(uri)?;
+        let path = uri_to_path(uri);
+        let mut opts = self.opts.clone();
+        if opts.max_width.is_none() {
+            let dir = parent_dir(&path);
+            let dir = if dir.is_dir() { dir } else { Path::new(".") };
+            opts.max_width = rustfmt_max_width(dir)?;
+        }
+        // smoelius: The program a backend runs can depend on the document's path. So the backend
+        // is checked for each document.
+        check_backend(&opts, &[&path])?;
+        let chunks = chunk(text).into_iter().filter(predicate).collect();
+        format_chunks(opts, &path, chunks)
+    }
+
+    fn text(&self, uri: &str) -> Result<&str> {
+        self.documents
+            .get(uri)
+            .map(String::as_str)
+            .ok_or_else(|| anyhow!("document is not open: {uri}"))
+    }
+}
+
+fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line_wc(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            content_length = Some(value.trim().parse::<usize>()?);
+        }
+    }
+    let content_length =
+        content_length.ok_or_else(|| anyhow!("message has no `Content-Length` header"))?;
+    let mut content = vec![0; content_length];
+    reader.read_exact_wc(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(Into::into)
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) -> Result<()> {
+    let content = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    writer.flush_wc()
+}
+
+fn error_response(id: &Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message,
+        },
+    })
+}
+
+fn publish_diagnostics(uri: &str, formatted_chunks: &[FormattedChunk]) -> Value {
+    let diagnostics = formatted_chunks
+        .iter()
+        .filter(|formatted_chunk| formatted_chunk.is_changed())
+        .map(diagnostic)
+        .collect::<Vec<_>>();
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {
+            "uri": uri,
+            "diagnostics": diagnostics,
+        },
+    })
+}
+
+fn diagnostic(formatted_chunk: &FormattedChunk) -> Value {
+    json!({
+        "range": range(&formatted_chunk.chunk.lines),
+        "severity": DIAGNOSTIC_SEVERITY_WARNING,
+        "source": env!("CARGO_PKG_NAME"),
+        "message": "doc comment is not formatted",
+    })
+}
+
+/// Returns an edit that replaces `formatted_chunk` in `text`, the text of the document the chunk
+/// came from
+fn text_edit(text: &str, formatted_chunk: &FormattedChunk) -> Value {
+    json!({
+        "range": range(&formatted_chunk.chunk.lines),
+        "newText": with_line_endings(text, &formatted_chunk.docs),
+    })
+}
+
+/// Converts a chunk's one-based, half-open line range to an LSP range
+fn range(lines: &Range<usize>) -> Value {
+    let lines = zero_based(lines);
+    json!({
+        "start": { "line": lines.start, "character": 0 },
+        "end": { "line": lines.end, "character": 0 },
+    })
+}
+
+fn zero_based(lines: &Range<usize>) -> Range<usize> {
+    lines.start - 1..lines.end - 1
+}
+
+/// Returns the zero-based, inclusive range of lines that an LSP range touches
+fn line_range(range: &Value) -> Result<RangeInclusive<usize>> {
+    let line = |position: &Value| {
+        position["line"]
+            .as_u64()
+            .and_then(|line| usize::try_from(line).ok())
+            .ok_or_else(|| anyhow!("invalid position: {position}"))
+    };
+    Ok(line(&range["start"])?..=line(&range["end"])?)
+}
+
+fn text_document_uri(params: &Value) -> Result<&str> {
+    params["textDocument"]["uri"]
+        .as_str()
+        .ok_or_else(|| anyhow!("request has no text document"))
+}
+
+/// Converts a `file:` URI to a path
+///
+/// The path is used to find a `rustfmt.toml` file and in error messages, so a URI with another
+/// scheme is converted to a path verbatim.
+fn uri_to_path(uri: &str) -> PathBuf {
+    let Some(path) = uri.strip_prefix("file://") else {
+        return PathBuf::from(uri);
+    };
+    // smoelius: On Windows, `file:///C:/foo` refers to `C:/foo`.
+    let path = if cfg!(windows) {
+        path.strip_prefix('/').unwrap_or(path)
+    } else {
+        path
+    };
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    PathBuf::from(&*String::from_utf8_lossy(&decoded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg_attr(windows, ignore = "testing on Unix-like platforms is sufficient")]
+    #[test]
+    fn uri_to_path_decodes_percent_escapes() {
+        assert_eq!(
+            PathBuf::from("/tmp/a b/lib.rs"),
+            uri_to_path("file:///tmp/a%20b/lib.rs")
+        );
+    }
+}
@@ -6,40 +6,74 @@ use anyhow::{Context, Result, anyhow, bail, ensure};
 use elaborate::std::{
     env::current_dir_wc,
     fs::read_to_string_wc,
-    io::WriteContext,
+    io::{ReadContext, WriteContext},
     path::PathContext,
-    process::{ChildContext, CommandContext, ExitStatusContext},
+    process::{CommandContext, ExitStatusContext},
     thread::available_parallelism_wc,
 };
-use glob::{GlobError, MatchOptions, glob_with};
+use glob::{GlobError, MatchOptions, Pattern, glob_with};
 use itertools::Itertools;
 use methodify::methodify;
 use rewriter::{Backup, LineColumn, Rewriter, Span};
 use std::{
+    borrow::Cow,
     collections::HashSet,
     env,
-    fs::{read_to_string, write},
+    ffi::OsStr,
+    fs::read_to_string,
     io,
     ops::Range,
-    path::Path,
-    process::{Child, Command, ExitStatus, Stdio},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
     sync::{
         Condvar, LazyLock, Mutex, MutexGuard,
         atomic::{AtomicBool, Ordering},
-        mpsc::{Receiver, SyncSender, sync_channel},
+        mpsc::{Receiver, RecvError, SyncSender, sync_channel},
     },
     thread,
     time::{Duration, Instant},
 };
 
+mod atomic_write;
+use atomic_write::write_atomically;
+
+mod backend;
+use backend::{Backend, Job};
+
+mod config;
+
+mod doctor;
+
+mod file_lines;
+use file_lines::FileLines;
+
+mod git;
+
+mod install_hook;
+
+mod interactive;
+
+mod lsp;
+
+mod out_dir;
+use out_dir::OutDir;
+
+mod pipe;
+
+mod print_config;
+
+mod report;
+
 mod resolve_project_file;
 use resolve_project_file::resolve_project_file;
 
-const PRETTIER: &str = if cfg!(windows) {
-    "prettier.cmd"
-} else {
-    "prettier"
-};
+mod staged;
+
+mod undo;
+
+mod watch;
+
+const BOM: &str = "\u{feff}";
 
 #[rustfmt::skip]
 const HELP: &str = "\
@@ -65,6 +99,175 @@ rustdoc-prettier supports glob patterns. Example:
 
     rustdoc-prettier '**/*.rs'
 
+rustdoc-prettier reads settings from a `rustdoc-prettier.toml`
+or `.rustdoc-prettier.toml` file in the current directory or its
+nearest ancestor. The `backend` key selects the markdown
+formatter with which doc comments are formatted. Example:
+
+    backend = \"prettier\"
+
+The backends are `prettier`, which is the default; `native`,
+which wraps prose without running an external program; and
+`dprint`, which runs `dprint fmt --stdin <FILE>.rustdoc.md`,
+where `<FILE>` is the source file's path. The backend can also
+be selected with `--backend <NAME>`. The `native` backend
+accepts no `prettier` options other than `--check`.
+
+The `dprint` backend requires a `dprint.json` file that includes
+dprint's markdown plugin, in the source file's directory or its
+nearest ancestor that has one. When a maximum width applies, the
+markdown plugin's `lineWidth` is set to the computed width, and
+its `textWrap` is set to `always`, by a configuration file that
+extends the `dprint.json` file. The generated file is written to
+a temporary directory and removed once it is no longer needed.
+
+The `prettier` program is the first of the following that
+applies: the path given by `--prettier-path <PATH>`; the path
+given by the `PRETTIER` environment variable; the
+`node_modules/.bin/prettier` nearest the source file, i.e., in
+the source file's directory or an ancestor; or `prettier` in
+`PATH`. `--version` reports which `prettier` was chosen.
+
+The `prettier` key of the configuration file is a semver version
+requirement. If the version of the `prettier` program chosen for
+any source file does not match the requirement, rustdoc-prettier
+fails without formatting any files, unless
+`--ignore-prettier-version` is given. Example:
+
+    prettier = \"^3.3\"
+
+Each doc comment is passed to `prettier` as though it came from
+a markdown file beside its source file, named, e.g.,
+`lib.rs.rustdoc.md` for `lib.rs`. So `prettier` applies the
+configuration files, `overrides`, and `.prettierignore` files
+that apply to that path. The `--prose-wrap` and `--print-width`
+options that rustdoc-prettier computes take precedence over a
+configuration file's `proseWrap` and `printWidth`.
+
+If `-` is given in place of source files, a source file is read
+from standard input, and the result is written to standard
+output.
+
+The `lsp` subcommand starts a Language Server Protocol server
+that communicates over standard input and output. The server
+supports range formatting and a code action to reformat the doc
+comment under the cursor, and it reports unformatted doc comments
+as diagnostics when a document is opened or saved. Example:
+
+    rustdoc-prettier lsp
+
+The `doctor` subcommand reports the `rustfmt.toml` file in
+effect, the `prettier` configuration file found for a path (by
+default, the current directory), the widths to which doc comments
+are formatted at sample indentations, and whether `prettier` runs.
+It also checks that `node` can load the plugins named by any
+`--plugin` options. If something is wrong, `doctor` gives advice
+and exits with a nonzero status. Example:
+
+    rustdoc-prettier doctor src/lib.rs --plugin prettier-plugin-x
+
+`--print-config <FILE>` prints the settings with which the doc
+comments in `FILE` would be formatted: the configuration files
+found, whether the maximum width came from `max_width` or
+`comment_width` and how it was adjusted, and the exact `prettier`
+command run for each kind and indentation of doc comment in the
+file.
+
+The `install-hook` subcommand installs a git pre-commit hook
+that runs rustdoc-prettier with `--staged` (see below) and any
+given `--max-width`, `--backend`, `--prettier-path`,
+`--ignore-prettier-version`, and `prettier` options. Other
+options are rejected. If a pre-commit hook already exists, the
+command is added to it, before a trailing `exit` or `exec` if
+there is one. Examples:
+
+    rustdoc-prettier install-hook           # fix and restage
+    rustdoc-prettier install-hook --check   # check only
+
+If `--watch` is given, rustdoc-prettier keeps running after it
+formats the source files, and reformats each source file when
+the file changes. `--watch` is supported only on Linux.
+
+If `--file-lines <JSON>` is given, only doc comments that
+intersect the given line ranges are formatted. The JSON has the
+same form as for rustfmt's `--file-lines` option. Example:
+
+    --file-lines '[{\"file\":\"src/lib.rs\",\"range\":[7,13]}]'
+
+If `--changed-since <REV>` is given, only doc comments that
+intersect lines added or modified since git revision `REV` are
+formatted. If no source files are given, the source files that
+changed since `REV` are formatted. Example:
+
+    rustdoc-prettier --changed-since origin/main
+
+If `--staged` is given, the source files staged in the git index
+are formatted in the index. A file in the working tree is updated
+only if it has no unstaged changes. Thus, `--staged` is suitable
+for use in a pre-commit hook.
+
+If `--interactive` is given, each doc comment that would change
+is shown together with its formatted version, and the user is
+asked whether to accept the change. Answers are read from
+standard input one line at a time:
+
+    y - accept this change
+    n - reject this change
+    e - edit the formatted doc comment, and accept the result
+    a - accept this change and all remaining changes in this file
+    q - quit; reject this change and all remaining changes
+
+Each source file is written by writing a temporary file in the
+same directory and renaming it over the source file, so a source
+file is never partially written. The source file's permissions,
+line endings, and byte order mark (if any) are preserved. If a
+source file is a symbolic link, the file the link refers to is
+written, and the link is left intact.
+
+rustdoc-prettier writes no source files until every source file
+has been formatted successfully. If formatting or writing any
+source file fails, no source files are modified. If
+`--no-transaction` is given, each source file is written as soon
+as it has been formatted.
+
+Each run that writes source files records their original
+contents in `target/rustdoc-prettier/last-run`, relative to the
+current directory. If `--undo` is given, the source files written
+by the last such run are restored. A source file that has changed
+since the run is not restored.
+
+If `--out-dir <DIR>` is given, no source files are modified.
+Instead, each source file whose doc comments would change is
+written, formatted, to the same relative path under `DIR`. If
+`--copy-unchanged` is also given, the remaining source files are
+copied to `DIR` as well. Source files must be below the current
+directory.
+
+If `--list-different` or `-l` is given, the source files whose
+doc comments would change are listed, and no files are written.
+
+If `--message-format json` is given, a JSON object is written to
+standard output for each source file. The object's `status` is
+one of `unchanged`, `reformatted`, `would-reformat`,
+`skipped-not-found`, or `error`. The object also gives the line
+ranges of the doc comments that changed, the maximum width
+(`max_width`, from which each doc comment's print width is
+computed, or `null` if none applies), and how long formatting
+the file took, in seconds.
+
+If `--emit sarif` is given, a SARIF 2.1.0 log is written to
+standard output. The log has a result for each doc comment that
+would change, and each result has a fix that replaces the doc
+comment with its formatted version. As with `--list-different`,
+no files are written.
+
+Similarly, `--emit checkstyle` writes checkstyle XML, and
+`--emit github` writes GitHub Actions workflow commands that
+annotate each doc comment that would change. `--emit rustfix`
+writes a JSON diagnostic for each doc comment that would change,
+in the format that rustc uses and that rustfix understands. Each
+diagnostic has a suggestion that replaces the doc comment.
+
 References
 
 - https://prettier.io/docs/en/options.html
@@ -73,36 +276,148 @@ References
 
 #[derive(Clone, Default)]
 struct Options {
+    /// Subcommand to run instead of formatting source files
+    subcommand: Option<Subcommand>,
     /// Preferred maximum width of a formatted line
     max_width: Option<usize>,
     /// Source files to format
     patterns: Vec<String>,
+    /// Where the source files to format come from
+    input: Input,
     /// Whether `args` includes `--check` and thus files should not be overwritten
     check: bool,
+    /// Whether to reformat source files when they change
+    watch: bool,
+    /// Whether to ask the user to accept or reject each reformatted doc comment
+    interactive: bool,
+    /// When to write formatted files
+    writes: Writes,
+    /// Directory to which to write formatted files, instead of overwriting the source files
+    out_dir: Option<OutDir>,
+    /// Format of a machine-readable report to write to standard output
+    report: Option<report::Format>,
+    /// Line ranges to which formatting is limited
+    file_lines: Option<FileLines>,
+    /// Git revision such that only lines changed since the revision should be formatted
+    changed_since: Option<String>,
+    /// The backend with which to format doc comments
+    backend: Option<backend::Kind>,
+    /// The `prettier` program given by `--prettier-path`
+    prettier_path: Option<PathBuf>,
+    /// The versions of `prettier` that may be used
+    prettier_version: PrettierVersion,
     /// Arguments to pass to `prettier`
     args: Vec<String>,
 }
 
+#[derive(Clone, Debug, Default)]
+enum PrettierVersion {
+    /// Any version, because the configuration file has no `prettier` requirement
+    #[default]
+    Any,
+    /// The versions allowed by the configuration file's requirement
+    Required(semver::VersionReq),
+    /// Any version, because `--ignore-prettier-version` was given
+    Ignored,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Subcommand {
+    Doctor,
+    InstallHook,
+    Lsp,
+    PrintConfig,
+    Undo,
+    Version,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum Writes {
+    /// Write no files until every file has been formatted successfully
+    #[default]
+    Transactional,
+    /// Write each file as soon as it has been formatted
+    Streaming,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum Input {
+    /// The source files matching `patterns`
+    #[default]
+    Files,
+    /// A source file read from standard input, with the result written to standard output
+    Stdin,
+    /// The source files staged in the git index
+    Staged,
+}
+
 #[derive(Debug)]
 struct Chunk {
     lines: Range<usize>,
     characteristics: Characteristics,
+    /// The chunk's lines as they appear in the source file
+    source: String,
+    /// The chunk's lines with indentation and `//!` or `///` syntax removed
+    docs: String,
+}
+
+/// A [`Chunk`] together with its formatted docs
+#[derive(Debug)]
+struct FormattedChunk {
+    chunk: Chunk,
+    /// The formatted docs, with indentation and `//!` or `///` syntax restored
     docs: String,
 }
 
+impl FormattedChunk {
+    fn is_changed(&self) -> bool {
+        self.chunk.source != self.docs
+    }
+}
+
+/// The result of formatting a source file
+struct FormattedSource {
+    /// The source file's contents before formatting
+    original: String,
+    /// The source file's formatted contents
+    contents: String,
+    /// The chunks that were formatted, whether or not they changed
+    formatted_chunks: Vec<FormattedChunk>,
+}
+
+/// The outcome of formatting a source file matched by a pattern
+struct FileOutcome {
+    path: PathBuf,
+    /// `Ok(None)` if the file disappeared before it could be formatted
+    result: Result<Option<FormattedSource>>,
+    /// Whether the file's formatted contents were written to the file
+    written: bool,
+    /// How long formatting the file took
+    duration: Duration,
+}
+
 /// Describes doc comments that need formatting
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 struct Characteristics {
     indent: usize,
     kind: DocKind,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 enum DocKind {
     Inner,
     Outer,
 }
 
+impl DocKind {
+    fn syntax(self) -> &'static str {
+        match self {
+            Self::Inner => "//!",
+            Self::Outer => "///",
+        }
+    }
+}
+
 static N_THREADS: LazyLock<usize> = LazyLock::new(|| {
     std::cmp::max(
         1,
@@ -117,20 +432,155 @@ fn main() -> Result<()> {
     let Some(mut opts) = process_args()? else {
         return Ok(());
     };
+    match opts.subcommand {
+        Some(Subcommand::Doctor) => return doctor::doctor(&opts),
+        Some(Subcommand::InstallHook) => return install_hook::install_hook(&opts),
+        Some(Subcommand::Lsp) => return lsp::serve(opts),
+        Some(Subcommand::PrintConfig) => return print_config::print_config(&opts),
+        Some(Subcommand::Undo) => return undo::undo(),
+        Some(Subcommand::Version) => return version(&opts),
+        None => {}
+    }
     if opts.max_width.is_none() {
-        opts.max_width = rustfmt_max_width()?;
+        opts.max_width = rustfmt_max_width(&current_dir_wc()?)?;
+    }
+
+    check_options(&opts)?;
+
+    match opts.input {
+        Input::Files => {}
+        Input::Stdin => return format_stdin(opts),
+        Input::Staged => return staged::format_staged(&opts),
     }
 
-    check_if_prettier_is_installed().with_context(|| "failed to run `prettier`")?;
+    if let Some(rev) = &opts.changed_since {
+        let file_lines = git::changed_lines(rev)?;
+        if opts.patterns.is_empty() {
+            opts.patterns = source_file_patterns(&file_lines)?;
+        }
+        opts.file_lines = Some(file_lines);
+    }
+
+    if opts.report.is_some() {
+        // smoelius: Whether a file would be reformatted is determined by comparing `prettier`'s
+        // output to the file's contents, so a `--check` option is not forwarded.
+        opts.args.retain(|arg| arg != "--check");
+    }
+
+    // smoelius: Split off `opts.patterns` so that its contents are not cloned before each call to
+    // `thread::spawn`.
+    let patterns = opts.patterns.split_off(0);
+    let paths = expand_patterns(patterns)?;
+
+    // smoelius: Watches are registered before the initial formatting pass so that a change made
+    // during the pass is not missed.
+    let watcher = if opts.watch {
+        Some(watch::Watcher::new(&paths)?)
+    } else {
+        None
+    };
+
+    let outcomes = format_files(&opts, paths)?;
+
+    if let Some(report) = opts.report {
+        return report::report(&opts, report, &outcomes);
+    }
+
+    if let Some(watcher) = watcher {
+        let formatted_contents = outcomes
+            .into_iter()
+            .filter_map(|outcome| {
+                let formatted_source = outcome.result.ok()??;
+                Some((outcome.path, formatted_source.contents))
+            })
+            .collect();
+        return watcher.watch(&opts, formatted_contents);
+    }
+
+    Ok(())
+}
 
+/// Formats the source files at `paths`
+///
+/// If `opts.report` is `None`, the first error encountered is returned. Otherwise, errors are
+/// returned as part of the files' outcomes.
+fn format_files(opts: &Options, paths: Vec<PathBuf>) -> Result<Vec<FileOutcome>> {
+    check_backend(opts, &paths)?;
+
+    let mut backups = Vec::new();
+    let mut handles = Vec::new();
+    let mut outcomes = Vec::new();
+    for path in paths {
+        let Some(backup) = Backup::new(&path)
+            .treat_einval_as_not_found_on_macos(&path)
+            .treat_deleted_path_error_as_not_found_on_windows(&path)
+            .ignore_not_found(|| format!("failed while backing up `{}`", path.display()))?
+        else {
+            outcomes.push(FileOutcome {
+                path,
+                result: Ok(None),
+                written: false,
+                duration: Duration::ZERO,
+            });
+            continue;
+        };
+        backups.push(backup);
+        let opts_clone = opts.clone();
+        let path_clone = path.clone();
+        let handle = thread::spawn(move || {
+            let instant = Instant::now();
+            let result = if opts_clone.out_dir.is_some() {
+                read_and_format_file(opts_clone, &path_clone)
+            } else {
+                match opts_clone.writes {
+                    Writes::Transactional => read_and_format_file(opts_clone, &path_clone),
+                    Writes::Streaming => format_file(opts_clone, &path_clone),
+                }
+            };
+            Ok((result, instant.elapsed()))
+        });
+        // smoelius: In interactive mode, files are formatted one at a time so that the user is
+        // asked about them in a predictable order.
+        if opts.interactive {
+            outcomes.push(join_outcome(opts, path, handle)?);
+        } else {
+            handles.push((path, handle));
+        }
+    }
+
+    for (path, handle) in handles {
+        outcomes.push(join_outcome(opts, path, handle)?);
+    }
+
+    if let Some(out_dir) = &opts.out_dir {
+        out_dir::write(out_dir, &outcomes)?;
+    } else if opts.writes == Writes::Transactional {
+        commit(opts, &mut outcomes)?;
+    }
+
+    undo::record(&outcomes)?;
+
+    // smoelius: Each backup restores its file when dropped. So if an error occurred above, any file
+    // that was written is restored. Disabling the backups keeps the files as written.
+    for mut backup in backups {
+        let _: Option<()> = backup
+            .disable()
+            .treat_deleted_path_error_as_not_found_on_windows(backup.path())
+            .ignore_not_found(|| String::from("failed while disabling backup"))?;
+    }
+
+    Ok(outcomes)
+}
+
+/// Returns the paths of the files matching `patterns`
+///
+/// A file matched by more than one pattern is returned only once.
+fn expand_patterns(patterns: Vec<String>) -> Result<Vec<PathBuf>> {
     // Overlapping patterns can match the same file. Schedule each file only once to avoid
     // sharing violations on Windows.
     let mut scheduled_paths = HashSet::new();
-    let mut backups = Vec::new();
-    let mut handles = Vec::new();
-    // smoelius: Split off `opts.patterns` so that its contents are not cloned before each call to
-    // `thread::spawn`.
-    for pattern in opts.patterns.split_off(0) {
+    let mut paths = Vec::new();
+    for pattern in patterns {
         let mut found = false;
         let match_options = MatchOptions {
             require_literal_leading_dot: true,
@@ -147,57 +597,218 @@ fn main() -> Result<()> {
             else {
                 continue;
             };
-            if scheduled_paths.contains(&path) {
-                found = true;
-                continue;
-            }
-            let Some(backup) = Backup::new(&path)
-                .treat_einval_as_not_found_on_macos(&path)
-                .treat_deleted_path_error_as_not_found_on_windows(&path)
-                .ignore_not_found(|| format!("failed while backing up `{}`", path.display()))?
-            else {
-                continue;
-            };
-            scheduled_paths.insert(path.clone());
-            backups.push(backup);
-            let opts = opts.clone();
-            handles.push(thread::spawn(|| format_file(opts, path)));
             found = true;
+            if scheduled_paths.insert(path.clone()) {
+                paths.push(path);
+            }
         }
         ensure!(found, "found no files matching pattern: {pattern}");
     }
+    Ok(paths)
+}
 
-    for handle in handles {
-        join_anyhow(handle)?;
+/// Writes the formatted contents of each file in `outcomes`, provided that every file was
+/// formatted successfully
+///
+/// If writing any file fails, an error is returned. In that case, the caller's backups restore the
+/// files that were written.
+fn commit(opts: &Options, outcomes: &mut [FileOutcome]) -> Result<()> {
+    if opts.check || outcomes.iter().any(|outcome| outcome.result.is_err()) {
+        return Ok(());
     }
-    for mut backup in backups {
-        let _: Option<()> = backup
-            .disable()
-            .treat_deleted_path_error_as_not_found_on_windows(backup.path())
-            .ignore_not_found(|| String::from("failed while disabling backup"))?;
+    for outcome in outcomes {
+        let Ok(Some(formatted_source)) = &outcome.result else {
+            continue;
+        };
+        outcome.written = write_formatted_source(&outcome.path, formatted_source)?.is_some();
+    }
+    Ok(())
+}
+
+/// Waits for the thread formatting the file at `path` to finish, and returns the file's outcome
+///
+/// If `opts.report` is `None` and formatting the file failed, the error is returned.
+fn join_outcome(
+    opts: &Options,
+    path: PathBuf,
+    handle: thread::JoinHandle<Result<(Result<Option<FormattedSource>>, Duration)>>,
+) -> Result<FileOutcome> {
+    let (result, duration) = join_anyhow(handle)?;
+    let result = match result {
+        Err(error) if opts.report.is_none() => return Err(error),
+        result => result,
+    };
+    // smoelius: When writes are transactional, files are written only after every file has been
+    // formatted. See `commit`.
+    let written =
+        opts.writes == Writes::Streaming && opts.out_dir.is_none() && !opts.check && result.is_ok();
+    Ok(FileOutcome {
+        path,
+        result,
+        written,
+        duration,
+    })
+}
+
+/// Checks that `opts` does not combine incompatible options
+fn check_options(opts: &Options) -> Result<()> {
+    match opts.input {
+        Input::Files => {}
+        Input::Stdin => {
+            ensure!(
+                opts.patterns.is_empty(),
+                "`-` cannot be used together with source files"
+            );
+            ensure!(
+                opts.changed_since.is_none(),
+                "`-` cannot be used together with `--changed-since`"
+            );
+        }
+        Input::Staged => {
+            ensure!(
+                opts.patterns.is_empty(),
+                "`--staged` cannot be used together with source files"
+            );
+            ensure!(
+                opts.changed_since.is_none() && !opts.watch,
+                "`--staged` cannot be used together with `--changed-since` or `--watch`"
+            );
+        }
+    }
+
+    if opts.report.is_some() {
+        ensure!(
+            opts.input == Input::Files && !opts.watch,
+            "`--emit`, `--list-different`, and `--message-format` cannot be used together with \
+             `-`, `--staged`, or `--watch`"
+        );
+    }
+
+    if opts.interactive {
+        ensure!(
+            opts.input == Input::Files && opts.report.is_none() && !opts.check && !opts.watch,
+            "`--interactive` can be used only when formatting source files in place"
+        );
     }
+
+    if opts.out_dir.is_some() {
+        ensure!(
+            opts.input == Input::Files && opts.report.is_none() && !opts.check && !opts.watch,
+            "`--out-dir` cannot be used together with `-`, `--staged`, `--check`, `--watch`, \
+             `--emit`, `--list-different`, or `--message-format`"
+        );
+    }
+
+    if opts.changed_since.is_some() {
+        ensure!(
+            opts.file_lines.is_none(),
+            "`--changed-since` cannot be used together with `--file-lines`"
+        );
+    }
+
+    if opts.watch {
+        ensure!(
+            cfg!(target_os = "linux"),
+            "`--watch` is supported only on Linux"
+        );
+        ensure!(
+            !opts.check,
+            "`--watch` cannot be used together with `--check`"
+        );
+    }
+
     Ok(())
 }
 
+/// Returns patterns matching the source files in `file_lines`
+///
+/// Where possible, the patterns are relative to the current directory.
+fn source_file_patterns(file_lines: &FileLines) -> Result<Vec<String>> {
+    let current_dir = current_dir_wc()?.canonicalize_wc()?;
+    let mut patterns = Vec::new();
+    for path in file_lines.files() {
+        if path
+            .extension_wc()
+            .ok()
+            .is_none_or(|extension| extension != "rs")
+            || !path.is_file()
+        {
+            continue;
+        }
+        let path = path.strip_prefix_wc(&current_dir).unwrap_or(path);
+        patterns.push(Pattern::escape(path.to_str_wc()?));
+    }
+    patterns.sort();
+    Ok(patterns)
+}
+
 fn process_args() -> Result<Option<Options>> {
     let mut opts = Options::default();
-    let mut iter = env::args().skip(1);
+    let mut iter = env::args().skip(1).peekable();
+    let mut copy_unchanged = false;
+    if iter.next_if_eq("install-hook").is_some() {
+        opts.subcommand = Some(Subcommand::InstallHook);
+    } else if iter.next_if_eq("lsp").is_some() {
+        opts.subcommand = Some(Subcommand::Lsp);
+    } else if iter.next_if_eq("doctor").is_some() {
+        opts.subcommand = Some(Subcommand::Doctor);
+        if let Some(path) = iter.next_if(|arg| !arg.starts_with('-')) {
+            opts.patterns.push(path);
+        }
+    }
     while let Some(arg) = iter.next() {
         if arg == "--help" || arg == "-h" {
             println!("{HELP}");
             return Ok(None);
-        } else if arg == "--max-width" {
-            let Some(arg) = iter.next() else {
-                bail!("missing argument to --max--width");
-            };
-            let width = arg.parse()?;
-            opts.max_width = Some(width);
-        } else if let Some(arg) = arg.strip_prefix("--max-width=") {
-            let width = arg.parse()?;
+        } else if let Some(value) = option_value("--max-width", &arg, &mut iter)? {
+            let width = value.parse()?;
             opts.max_width = Some(width);
         } else if arg == "--version" || arg == "-V" {
-            version();
-            return Ok(None);
+            opts.subcommand = Some(Subcommand::Version);
+        } else if arg == "-" {
+            opts.set_input(Input::Stdin)?;
+        } else if let Some(value) = option_value("--emit", &arg, &mut iter)? {
+            opts.report = report::Format::parse_emit(&value)?;
+            // smoelius: The report's locations refer to the source files as they were read, so the
+            // files must not be overwritten.
+            if opts.report.is_some() {
+                opts.check = true;
+            }
+        } else if arg == "--list-different" || arg == "-l" {
+            opts.report = Some(report::Format::ListDifferent);
+            opts.check = true;
+        } else if let Some(value) = option_value("--message-format", &arg, &mut iter)? {
+            opts.report = report::Format::parse_message_format(&value)?;
+        } else if arg == "--staged" {
+            opts.set_input(Input::Staged)?;
+        } else if arg == "--interactive" {
+            opts.interactive = true;
+        } else if arg == "--undo" {
+            opts.subcommand = Some(Subcommand::Undo);
+        } else if let Some(value) = option_value("--print-config", &arg, &mut iter)? {
+            opts.subcommand = Some(Subcommand::PrintConfig);
+            opts.patterns.push(value);
+        } else if let Some(value) = option_value("--out-dir", &arg, &mut iter)? {
+            opts.out_dir = Some(OutDir {
+                path: PathBuf::from(value),
+                copy_unchanged: false,
+            });
+        } else if arg == "--copy-unchanged" {
+            copy_unchanged = true;
+        } else if let Some(value) = option_value("--backend", &arg, &mut iter)? {
+            opts.backend = Some(backend::Kind::parse(&value)?);
+        } else if let Some(value) = option_value("--prettier-path", &arg, &mut iter)? {
+            opts.prettier_path = Some(PathBuf::from(value));
+        } else if arg == "--ignore-prettier-version" {
+            opts.prettier_version = PrettierVersion::Ignored;
+        } else if arg == "--no-transaction" {
+            opts.writes = Writes::Streaming;
+        } else if arg == "--watch" {
+            opts.watch = true;
+        } else if let Some(value) = option_value("--file-lines", &arg, &mut iter)? {
+            opts.file_lines = Some(FileLines::parse(&value)?);
+        } else if let Some(value) = option_value("--changed-since", &arg, &mut iter)? {
+            opts.changed_since = Some(value);
         } else if arg.to_lowercase().ends_with(".rs") {
             opts.patterns.push(arg);
         } else {
@@ -207,26 +818,132 @@ fn process_args() -> Result<Option<Options>> {
             opts.args.push(arg);
         }
     }
+    if copy_unchanged {
+        let Some(out_dir) = &mut opts.out_dir else {
+            bail!("`--copy-unchanged` requires `--out-dir`");
+        };
+        out_dir.copy_unchanged = true;
+    }
+    let config = config::load(&current_dir_wc()?)?;
+    opts.backend = opts.backend.or(config.backend);
+    if !matches!(opts.prettier_version, PrettierVersion::Ignored)
+        && let Some(requirement) = config.prettier
+    {
+        opts.prettier_version = PrettierVersion::Required(requirement);
+    }
     Ok(Some(opts))
 }
 
-fn version() {
+/// Returns the value of the option `name` if `arg` is that option
+///
+/// The value is either part of `arg`, as in `--name=value`, or the next argument in `iter`.
+fn option_value(
+    name: &str,
+    arg: &str,
+    iter: &mut impl Iterator<Item = String>,
+) -> Result<Option<String>> {
+    if arg == name {
+        let Some(value) = iter.next() else {
+            bail!("missing argument to {name}");
+        };
+        return Ok(Some(value));
+    }
+    Ok(arg
+        .strip_prefix(name)
+        .and_then(|suffix| suffix.strip_prefix('='))
+        .map(ToOwned::to_owned))
+}
+
+impl Options {
+    fn set_input(&mut self, input: Input) -> Result<()> {
+        ensure!(
+            self.input == Input::Files || self.input == input,
+            "`-` cannot be used together with `--staged`"
+        );
+        self.input = input;
+        Ok(())
+    }
+
+    fn backend(&self) -> &'static dyn Backend {
+        self.backend.unwrap_or_default().backend()
+    }
+}
+
+fn version(opts: &Options) -> Result<()> {
     const RUSTDOC_PRETTIER_VERSION: &str = env!("CARGO_PKG_VERSION");
+    let resolved = backend::resolve_prettier(opts, &current_dir_wc()?);
     let node_version = program_version("node").unwrap_or_else(|_| String::from("??"));
-    let prettier_version = program_version(PRETTIER).unwrap_or_else(|_| String::from("??"));
+    let prettier_version =
+        program_version(&resolved.program).unwrap_or_else(|_| String::from("??"));
     println!(
         "rustdoc-prettier {RUSTDOC_PRETTIER_VERSION} (node {node_version}, prettier \
          {prettier_version})"
     );
+    println!(
+        "prettier: {} (found via {})",
+        resolved.program.display(),
+        resolved.source
+    );
+    Ok(())
 }
 
-fn rustfmt_max_width() -> Result<Option<usize>> {
-    let current_dir = current_dir_wc()?;
-    let Some(path) =
-        resolve_project_file(&current_dir).with_context(|| "failed to find `rustfmt.toml` file")?
-    else {
+/// Returns the maximum width implied by the `rustfmt.toml` file in `dir` or its nearest ancestor
+fn rustfmt_max_width(dir: &Path) -> Result<Option<usize>> {
+    let Some(path) = find_rustfmt_toml(dir)? else {
         return Ok(None);
     };
+    let width = rustfmt_toml_width(&path)?;
+    Ok(width.map(|width| width.max_width))
+}
+
+/// Returns the `rustfmt.toml` file in `dir` or its nearest ancestor
+fn find_rustfmt_toml(dir: &Path) -> Result<Option<PathBuf>> {
+    resolve_project_file(dir).with_context(|| "failed to find `rustfmt.toml` file")
+}
+
+/// Returns the first of `file_names` that is a file in `dir` or its nearest ancestor that has one
+///
+/// A relative `dir` is relative to the current directory. A file name can have more than one
+/// component, e.g., `node_modules/.bin/prettier`.
+fn find_nearest(dir: &Path, file_names: &[impl AsRef<Path>]) -> Result<Option<PathBuf>> {
+    let dir = dir.canonicalize_wc()?;
+    for ancestor in dir.ancestors() {
+        for file_name in file_names {
+            let path = ancestor.join(file_name);
+            if path.is_file() {
+                return Ok(Some(path));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the directory containing the file at `path`
+///
+/// If `path` has no parent, or its parent is empty, the current directory, `.`, is returned.
+fn parent_dir(path: &Path) -> &Path {
+    path.parent_wc()
+        .ok()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+}
+
+/// A maximum width read from a `rustfmt.toml` file
+struct RustfmtWidth {
+    /// The key the width was read from, i.e., `max_width` or `comment_width`
+    key: &'static str,
+    /// The key's value
+    value: usize,
+    /// The maximum width implied by the key's value
+    max_width: usize,
+    /// Whether the file also has a `comment_width` key, which was ignored in favor of `max_width`
+    comment_width_ignored: bool,
+}
+
+/// Returns the maximum width implied by the `rustfmt.toml` file at `path`
+///
+/// If the file has both a `max_width` and a `comment_width` key, `max_width` is used.
+fn rustfmt_toml_width(path: &Path) -> Result<Option<RustfmtWidth>> {
     let contents = read_to_string_wc(path)?;
     let table = contents.parse::<toml::Table>()?;
     if let Some(max_width) = table.get("max_width") {
@@ -234,7 +951,12 @@ fn rustfmt_max_width() -> Result<Option<usize>> {
             bail!("`max_width` is not an integer");
         };
         let max_width = usize::try_from(max_width_i64)?;
-        return Ok(Some(max_width));
+        return Ok(Some(RustfmtWidth {
+            key: "max_width",
+            value: max_width,
+            max_width,
+            comment_width_ignored: table.contains_key("comment_width"),
+        }));
     }
     if let Some(comment_width) = table.get("comment_width") {
         let Some(comment_width_i64) = comment_width.as_integer() else {
@@ -243,21 +965,33 @@ fn rustfmt_max_width() -> Result<Option<usize>> {
         let comment_width = usize::try_from(comment_width_i64)?;
         // smoelius: Work around off-by-one error in `rustfmt` `comment_width` implementation:
         // https://github.com/rust-lang/rustfmt/issues/6180
-        let comment_width = comment_width.saturating_sub(1);
-        return Ok(Some(comment_width));
+        return Ok(Some(RustfmtWidth {
+            key: "comment_width",
+            value: comment_width,
+            max_width: comment_width.saturating_sub(1),
+            comment_width_ignored: false,
+        }));
     }
     Ok(None)
 }
 
-fn check_if_prettier_is_installed() -> Result<()> {
-    program_version(PRETTIER).map(|_| ())
+/// Returns an error if the backend selected by `opts` cannot be used to format the files at
+/// `paths`
+fn check_backend(opts: &Options, paths: &[impl AsRef<Path>]) -> Result<()> {
+    let backend = opts.backend();
+    let paths = paths.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+    backend
+        .check(opts, &paths)
+        .with_context(|| format!("failed to run `{}`", backend.name()))
 }
 
-fn program_version(program: &str) -> Result<String> {
+fn program_version(program: impl AsRef<OsStr>) -> Result<String> {
+    let program = program.as_ref();
     let output = Command::new(program).arg("--version").output_wc()?;
     if !output.status.success() {
         bail!(
-            "`{program} --version` exited {}",
+            "`{} --version` exited {}",
+            program.display(),
             exit_status_adverbial(output.status)
         );
     }
@@ -266,40 +1000,160 @@ fn program_version(program: &str) -> Result<String> {
         .map_err(Into::into)
 }
 
-fn format_file(opts: Options, path: impl AsRef<Path>) -> Result<()> {
+fn format_stdin(opts: Options) -> Result<()> {
     let check = opts.check;
+    let path = Path::new("<stdin>");
+    check_backend(&opts, &[path])?;
+
+    let mut contents = String::new();
+    io::stdin().read_to_string_wc(&mut contents)?;
+
+    let formatted_source = format_source(opts, path, &contents)?;
+
+    if !check {
+        io::stdout().write_all_wc(formatted_source.contents.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Formats the doc comments in the file at `path`, and writes the result unless `--check` was
+/// given
+///
+/// Returns `None` if the file disappeared while it was being formatted.
+fn format_file(opts: Options, path: impl AsRef<Path>) -> Result<Option<FormattedSource>> {
+    let check = opts.check;
+    let Some(formatted_source) = read_and_format_file(opts, path.as_ref())? else {
+        return Ok(None);
+    };
+
+    if !check && write_formatted_source(path.as_ref(), &formatted_source)?.is_none() {
+        return Ok(None);
+    }
+
+    Ok(Some(formatted_source))
+}
+
+/// Formats the doc comments in the file at `path` without writing the result
+///
+/// Returns `None` if the file does not exist.
+fn read_and_format_file(opts: Options, path: &Path) -> Result<Option<FormattedSource>> {
     #[allow(clippy::disallowed_methods)]
-    let Some(contents) = read_to_string(&path)
-        .treat_deleted_path_error_as_not_found_on_windows(path.as_ref())
-        .ignore_not_found(|| format!("failed while reading `{}`", path.as_ref().display()))?
+    let Some(contents) = read_to_string(path)
+        .treat_deleted_path_error_as_not_found_on_windows(path)
+        .ignore_not_found(|| format!("failed while reading `{}`", path.display()))?
     else {
-        return Ok(());
+        return Ok(None);
     };
 
-    let chunks = chunk(&contents);
+    format_source(opts, path, &contents).map(Some)
+}
+
+/// Writes `formatted_source`'s contents to the file at `path`, if they differ from the file's
+/// original contents
+///
+/// Returns `None` if the file disappeared before it could be written.
+fn write_formatted_source(path: &Path, formatted_source: &FormattedSource) -> Result<Option<()>> {
+    // smoelius: Writing a file whose contents did not change would needlessly update its
+    // modification time, which can trigger rebuilds and file watchers.
+    if formatted_source.contents == formatted_source.original {
+        return Ok(Some(()));
+    }
+
+    write_atomically(path, &formatted_source.contents)
+        .treat_einval_as_not_found_on_macos(path)
+        .treat_deleted_path_error_as_not_found_on_windows(path)
+        .ignore_not_found(|| format!("failed while writing `{}`", path.display()))
+}
+
+/// Formats the doc comments in `contents`, which were read from `path`
+///
+/// `path` determines the `prettier` that is run and how it is configured, and is used in error
+/// messages.
+fn format_source(opts: Options, path: &Path, contents: &str) -> Result<FormattedSource> {
+    // smoelius: A byte order mark is set aside so that a doc comment on the first line is
+    // recognized, and so that the mark is preserved.
+    let (bom, body) = contents
+        .strip_prefix(BOM)
+        .map_or(("", contents), |body| (BOM, body));
+
+    let chunks = chunk(body)
+        .into_iter()
+        .filter(|chunk| {
+            opts.file_lines
+                .as_ref()
+                .is_none_or(|file_lines| file_lines.intersects(path, &chunk.lines))
+        })
+        .collect();
+
+    let interactive = opts.interactive;
+
+    let mut formatted_chunks = format_chunks(opts, path, chunks)?;
+
+    if interactive {
+        formatted_chunks = interactive::review(path, body, formatted_chunks)?;
+    }
+
+    Ok(FormattedSource {
+        original: contents.to_owned(),
+        contents: format!("{bom}{}", rewrite(body, &formatted_chunks)),
+        formatted_chunks,
+    })
+}
+
+/// Formats each of `chunks`, which were read from `path`
+fn format_chunks(opts: Options, path: &Path, chunks: Vec<Chunk>) -> Result<Vec<FormattedChunk>> {
     let characteristics = chunks
         .iter()
         .map(|chunk| chunk.characteristics)
         .collect::<Vec<_>>();
 
-    let (sender, receiver) = sync_channel::<Prettier>(*N_THREADS);
-    let handle = thread::spawn(move || prettier_spawner(&opts, &characteristics, &sender));
+    let (sender, receiver) = sync_channel::<Formatter>(*N_THREADS);
+    let path_buf = path.to_path_buf();
+    let handle =
+        thread::spawn(move || formatter_spawner(&opts, &path_buf, &characteristics, &sender));
 
-    let mut rewriter = Rewriter::new(&contents);
+    let mut formatted_chunks = Vec::with_capacity(chunks.len());
 
     for chunk in chunks {
         if CTRLC.load(Ordering::SeqCst) {
             bail!("Ctrl-C detected");
         }
 
-        let docs = format_chunk(&receiver, &chunk).with_context(|| {
-            format!(
-                "failed to format {}:{:?}",
-                path.as_ref().display(),
-                chunk.lines
-            )
-        })?;
+        let docs = match format_chunk(&receiver, &chunk) {
+            Ok(docs) => docs,
+            Err(error) => {
+                // smoelius: If `recv` failed, the spawner returned and dropped its sender, e.g.,
+                // because the backend could not be started. The spawner's error explains why.
+                if error.is::<RecvError>() {
+                    join_anyhow(handle)?;
+                }
+                return Err(error).with_context(|| {
+                    format!("failed to format {}:{:?}", path.display(), chunk.lines)
+                });
+            }
+        };
+
+        formatted_chunks.push(FormattedChunk { chunk, docs });
+    }
+
+    join_anyhow(handle)?;
+
+    Ok(formatted_chunks)
+}
 
+/// Replaces each chunk in `contents` with its formatted docs
+///
+/// Chunks that did not change are left as they are. The formatted docs use the line ending of
+/// `contents`' first line.
+fn rewrite(contents: &str, formatted_chunks: &[FormattedChunk]) -> String {
+    let mut rewriter = Rewriter::new(contents);
+
+    for formatted_chunk in formatted_chunks {
+        if !formatted_chunk.is_changed() {
+            continue;
+        }
+        let FormattedChunk { chunk, docs } = formatted_chunk;
         let start = LineColumn {
             line: chunk.lines.start,
             column: 0,
@@ -310,22 +1164,24 @@ fn format_file(opts: Options, path: impl AsRef<Path>) -> Result<()> {
         };
         let span = Span::new(start, end);
 
-        rewriter.rewrite(&span, &docs);
+        rewriter.rewrite(&span, &with_line_endings(contents, docs));
     }
 
-    let contents = rewriter.contents();
+    rewriter.contents()
+}
 
-    if !check {
-        #[allow(clippy::disallowed_methods)]
-        write(&path, contents)
-            .treat_einval_as_not_found_on_macos(path.as_ref())
-            .treat_deleted_path_error_as_not_found_on_windows(path.as_ref())
-            .ignore_not_found(|| format!("failed while writing `{}`", path.as_ref().display()))?;
+/// Returns `docs` with the line endings that `contents` uses
+///
+/// `contents` is considered to use CRLF line endings if its first line ends with one.
+fn with_line_endings<'a>(contents: &str, docs: &'a str) -> Cow<'a, str> {
+    let crlf = contents
+        .find('\n')
+        .is_some_and(|index| contents[..index].ends_with('\r'));
+    if crlf {
+        Cow::Owned(docs.replace('\n', "\r\n"))
+    } else {
+        Cow::Borrowed(docs)
     }
-
-    join_anyhow(handle)?;
-
-    Ok(())
 }
 
 /// Warns about and converts a macOS `EINVAL` error into an [`io::ErrorKind::NotFound`] error if
@@ -424,18 +1280,24 @@ fn ignore_not_found<T>(
 fn chunk(contents: &str) -> Vec<Chunk> {
     let mut line_curr = 1;
     let mut chunks = Vec::new();
-    for (key, key_line_pairs) in &contents
+    for (key, group) in &contents
         .lines()
-        .map(preprocess_line)
-        .chunk_by(|&(key, _)| key)
+        .map(|line| (preprocess_line(line), line))
+        .chunk_by(|&((key, _), _)| key)
     {
-        let lines = key_line_pairs.map(|(_key, line)| line).collect::<Vec<_>>();
+        let (lines, source_lines): (Vec<_>, Vec<_>) = group
+            .map(|((_key, line), source_line)| (line, source_line))
+            .unzip();
         let line_prev = line_curr;
         line_curr += lines.len();
         if let Some(characteristics) = key {
             chunks.push(Chunk {
                 lines: line_prev..line_curr,
                 characteristics,
+                source: source_lines
+                    .iter()
+                    .map(|line| format!("{line}\n"))
+                    .collect(),
                 docs: lines.iter().map(|line| format!("{line}\n")).collect(),
             });
         }
@@ -482,79 +1344,66 @@ fn preprocess_line(line: &str) -> (Option<Characteristics>, &str) {
     (Some(characteristics), &suffix[i..])
 }
 
-struct Prettier {
-    child: Child,
+struct Formatter {
+    job: Box<dyn Job>,
     decrement_used_parallelism: DecrementUsedParallelism,
 }
 
-/// Spawns a `prettier` instance for each element of `characteristics`, and sends the instance over
-/// `sender`
+/// Returns the width to which doc comments with `characteristics` are formatted, given the maximum
+/// width of a line
+///
+/// The width excludes the indentation, the `//!` or `///` syntax, and the space that might follow
+/// that syntax.
+fn effective_width(max_width: usize, characteristics: Characteristics) -> usize {
+    max_width.saturating_sub(characteristics.indent + characteristics.kind.syntax().len() + 1)
+}
+
+/// Starts a backend job for each element of `characteristics`, which were read from `path`, and
+/// sends the job over `sender`
 ///
-/// Note that `characteristics` influences the arguments passed to `prettier`. So the `prettier`
-/// instances must be consumed in the same order in which they were spawned.
-#[allow(clippy::unnecessary_wraps)]
-fn prettier_spawner(
+/// Note that `characteristics` influences the width passed to the backend. So the jobs must be
+/// consumed in the same order in which they were started.
+fn formatter_spawner(
     opts: &Options,
+    path: &Path,
     characteristics: &[Characteristics],
-    sender: &SyncSender<Prettier>,
+    sender: &SyncSender<Formatter>,
 ) -> Result<()> {
+    let backend = opts.backend();
     for &characteristics in characteristics {
         let mut used_parallelism = lock_used_parallelism_for_incrementing();
-        let mut command = Command::new(PRETTIER);
-        command.arg("--parser=markdown");
-        if let Some(max_width) = opts.max_width {
-            command.arg("--prose-wrap=always");
-            command.arg(format!(
-                "--print-width={}",
-                max_width.saturating_sub(characteristics.indent + 4)
-            ));
-        }
-        command.args(&opts.args);
-        command
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-        let child = command.spawn_wc().expect("failed to spawn `prettier`");
+        let width = opts
+            .max_width
+            .map(|max_width| effective_width(max_width, characteristics));
+        let job = backend
+            .start(opts, path, width)
+            .with_context(|| format!("failed to spawn `{}`", backend.name()))?;
         // smoelius: The `sender` channel is created with a capacity of `N_THREADS`, and no more
-        // than `N_THREADS` children exist at any time. For these reasons, the next `try_send`
-        // should fail only if prettier exits. In that case, we should unwind gracefully so that an
-        // error message returned elsewhere can be displayed to the user.
+        // than `N_THREADS` jobs exist at any time. For these reasons, the next `try_send` should
+        // fail only if a job fails. In that case, we should unwind gracefully so that an error
+        // message returned elsewhere can be displayed to the user.
         *used_parallelism += 1;
-        let prettier = Prettier {
-            child,
+        let formatter = Formatter {
+            job,
             decrement_used_parallelism: DecrementUsedParallelism,
         };
         drop(used_parallelism);
         sender
-            .try_send(prettier)
-            .with_context(|| "failed to send `prettier`")?;
+            .try_send(formatter)
+            .with_context(|| "failed to send formatter")?;
     }
     Ok(())
 }
 
-fn format_chunk(receiver: &Receiver<Prettier>, chunk: &Chunk) -> Result<String> {
-    let Prettier {
-        mut child,
+fn format_chunk(receiver: &Receiver<Formatter>, chunk: &Chunk) -> Result<String> {
+    let Formatter {
+        job,
         decrement_used_parallelism,
     } = receiver.recv()?;
-    let mut stdin = child
-        .stdin
-        .take()
-        .ok_or_else(|| anyhow!("child has no stdin"))?;
-
-    stdin.write_all_wc(chunk.docs.as_bytes())?;
-    drop(stdin);
-
-    let output = child.wait_with_output_wc()?;
-    ensure!(
-        output.status.success(),
-        "`prettier` exited {}",
-        exit_status_adverbial(output.status)
-    );
 
-    drop(decrement_used_parallelism);
+    let docs = job.finish(&chunk.docs)?;
 
-    let docs = String::from_utf8(output.stdout)?;
+    drop(decrement_used_parallelism);
 
     Ok(postprocess_docs(chunk.characteristics, &docs))
 }
@@ -599,10 +1448,7 @@ fn postprocess_docs(characteristics: Characteristics, docs: &str) -> String {
             format!(
                 "{:indent$}{}{}{}\n",
                 "",
-                match kind {
-                    DocKind::Inner => "//!",
-                    DocKind::Outer => "///",
-                },
+                kind.syntax(),
                 if line.is_empty() { "" } else { " " },
                 line,
             )
@@ -631,7 +1477,10 @@ mod tests {
 
     #[test]
     fn prettier_is_installed() {
-        check_if_prettier_is_installed().unwrap();
+        backend::Kind::Prettier
+            .backend()
+            .check(&Options::default(), &[Path::new("lib.rs")])
+            .unwrap();
     }
 
     // smoelius: `used_parallelism_is_decremented_when_format_chunk_fails` and
@@ -666,13 +1515,19 @@ mod tests {
 
     /// Spawns a `prettier` instance to check `chunk` and returns a [`Receiver`] from which it can
     /// be retrieved.
-    fn spawn_prettier_instance(chunk: &Chunk) -> Receiver<Prettier> {
+    fn spawn_prettier_instance(chunk: &Chunk) -> Receiver<Formatter> {
         let opts = Options {
             args: vec![String::from("--check")],
             ..Options::default()
         };
         let (sender, receiver) = sync_channel(1);
-        prettier_spawner(&opts, &[chunk.characteristics], &sender).unwrap();
+        formatter_spawner(
+            &opts,
+            Path::new("lib.rs"),
+            &[chunk.characteristics],
+            &sender,
+        )
+        .unwrap();
         assert_eq!(*USED_PARALLELISM.lock().unwrap(), 1);
         receiver
     }
@@ -0,0 +1,60 @@
+//! Write formatted source files to a separate directory, leaving the source files as they are
+
+use super::FileOutcome;
+use anyhow::{Result, bail};
+use elaborate::std::{
+    env::current_dir_wc,
+    fs::{create_dir_all_wc, write_wc},
+    path::PathContext,
+};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Clone, Debug)]
+pub struct OutDir {
+    pub path: PathBuf,
+    /// Whether to copy source files whose doc comments did not change
+    pub copy_unchanged: bool,
+}
+
+/// Writes the formatted contents of each file in `outcomes` to the same relative path under
+/// `out_dir`
+pub fn write(out_dir: &OutDir, outcomes: &[FileOutcome]) -> Result<()> {
+    let current_dir = current_dir_wc()?;
+    for outcome in outcomes {
+        let Ok(Some(formatted_source)) = &outcome.result else {
+            continue;
+        };
+        if formatted_source.contents == formatted_source.original && !out_dir.copy_unchanged {
+            continue;
+        }
+        let path = out_dir
+            .path
+            .join(relative_path(&current_dir, &outcome.path)?);
+        create_dir_all_wc(path.parent_wc()?)?;
+        write_wc(&path, &formatted_source.contents)?;
+    }
+    Ok(())
+}
+
+/// Returns `path` relative to `current_dir`
+///
+/// An error is returned if `path` is not below `current_dir`, because the file would then be
+/// written outside of the output directory.
+fn relative_path(current_dir: &Path, path: &Path) -> Result<PathBuf> {
+    let stripped = path.strip_prefix_wc(current_dir).unwrap_or(path);
+    let mut relative = PathBuf::new();
+    for component in stripped.components() {
+        match component {
+            Component::CurDir => {}
+            Component::Normal(name) => relative.push(name),
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                bail!(
+                    "`{}` is not below the current directory and cannot be written to the output \
+                     directory",
+                    path.display()
+                );
+            }
+        }
+    }
+    Ok(relative)
+}
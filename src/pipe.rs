@@ -0,0 +1,34 @@
+//! Run a program with a string as its standard input
+//!
+//! This module is shared by `rustdoc-prettier` and `rustfmt-prettier`.
+
+use anyhow::Result;
+use elaborate::std::{
+    io::WriteContext,
+    process::{ChildContext, CommandContext},
+};
+use std::{
+    process::{Command, Output, Stdio},
+    thread,
+};
+
+/// Runs `command` with `input` as its standard input, and returns its output
+///
+/// The command's standard output is captured. Its standard error is captured only if `command` was
+/// configured to pipe it.
+pub fn output_with_stdin(command: &mut Command, input: &str) -> Result<Output> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn_wc()?;
+    let mut stdin = child.stdin.take().unwrap();
+    // smoelius: Write to the child's standard input on a separate thread to avoid a deadlock should
+    // the child fill its standard output pipe.
+    let output = thread::scope(|scope| {
+        let handle = scope.spawn(move || stdin.write_all_wc(input.as_bytes()));
+        let output = child.wait_with_output_wc();
+        let result = handle.join().unwrap();
+        output.and_then(|output| result.map(|()| output))
+    })?;
+    Ok(output)
+}
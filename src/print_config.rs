@@ -0,0 +1,108 @@
+//! Print the settings with which the doc comments in a source file would be formatted
+//!
+//! The settings include the configuration files that were found, how the maximum width was
+//! determined, and the `prettier` command run for each kind and indentation of doc comment in the
+//! file.
+
+use super::{
+    BOM, Options, backend, chunk, config, effective_width, find_rustfmt_toml, parent_dir,
+    rustfmt_toml_width,
+};
+use anyhow::{Result, ensure};
+use elaborate::std::{env::current_dir_wc, fs::read_to_string_wc};
+use itertools::Itertools;
+use std::path::Path;
+
+pub fn print_config(opts: &Options) -> Result<()> {
+    ensure!(
+        opts.patterns.len() == 1,
+        "`--print-config` cannot be used together with source files"
+    );
+    let path = Path::new(&opts.patterns[0]);
+    let contents = read_to_string_wc(path)?;
+    let dir = parent_dir(path);
+
+    // smoelius: Both `rustdoc-prettier.toml` and `rustfmt.toml` are found relative to the current
+    // directory, not `path`.
+    let current_dir = current_dir_wc()?;
+    let config = config::load(&current_dir)?;
+    print_path("rustdoc-prettier.toml", config.path.as_deref());
+
+    let rustfmt_toml = find_rustfmt_toml(&current_dir)?;
+    print_path("rustfmt.toml", rustfmt_toml.as_deref());
+
+    let max_width = if let Some(max_width) = opts.max_width {
+        println!("max width: {max_width}, given by `--max-width`");
+        Some(max_width)
+    } else if let Some(rustfmt_toml) = &rustfmt_toml
+        && let Some(width) = rustfmt_toml_width(rustfmt_toml)?
+    {
+        let mut explanation = format!("from `{} = {}`", width.key, width.value);
+        if width.comment_width_ignored {
+            explanation.push_str("; `max_width` takes precedence over `comment_width`");
+        }
+        if width.max_width != width.value {
+            explanation.push_str(
+                "; 1 is subtracted to work around https://github.com/rust-lang/rustfmt/issues/6180",
+            );
+        }
+        println!("max width: {}, {explanation}", width.max_width);
+        Some(width.max_width)
+    } else {
+        println!("max width: none; neither `max_width` nor `comment_width` is set");
+        None
+    };
+
+    let backend = opts.backend();
+    println!("backend: {}", backend.name());
+    let is_prettier = opts.backend.unwrap_or_default() == backend::Kind::Prettier;
+    if is_prettier {
+        let resolved = backend::resolve_prettier(opts, dir);
+        println!(
+            "prettier: {} (found via {})",
+            resolved.program.display(),
+            resolved.source
+        );
+        let stdin_filepath = backend::stdin_filepath(path).unwrap_or_else(|| path.to_path_buf());
+        let config_path = backend::find_prettier_config_path(&resolved.program, &stdin_filepath);
+        println!(
+            "prettier config: {}",
+            config_path.as_deref().unwrap_or("none found")
+        );
+    }
+
+    let body = contents.strip_prefix(BOM).unwrap_or(&contents);
+    for characteristics in chunk(body)
+        .into_iter()
+        .map(|chunk| chunk.characteristics)
+        .unique()
+    {
+        let width = max_width.map(|max_width| effective_width(max_width, characteristics));
+        let label = format!(
+            "`{}` at indent {}",
+            characteristics.kind.syntax(),
+            characteristics.indent
+        );
+        if is_prettier {
+            let command = backend::prettier_command(opts, path, width);
+            let argv = std::iter::once(command.get_program())
+                .chain(command.get_args())
+                .collect::<Vec<_>>();
+            println!("{label}: {argv:?}");
+        } else if let Some(width) = width {
+            println!("{label}: width {width}");
+        } else {
+            println!("{label}: no width");
+        }
+    }
+
+    Ok(())
+}
+
+fn print_path(label: &str, path: Option<&Path>) {
+    if let Some(path) = path {
+        println!("{label}: {}", path.display());
+    } else {
+        println!("{label}: none found");
+    }
+}
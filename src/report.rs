@@ -0,0 +1,496 @@
+//! Machine-readable reports of the files a run formatted
+
+use super::{BOM, FileOutcome, FormattedChunk, Options, with_line_endings};
+use anyhow::{Result, bail, ensure};
+use elaborate::std::io::WriteContext;
+use serde_json::{Value, json};
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+/// Identifies the single kind of problem rustdoc-prettier reports
+const RULE_ID: &str = "unformatted-doc-comment";
+
+const MESSAGE: &str = "doc comment is not formatted";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// The paths of the files that would be reformatted, like `prettier --list-different`
+    ListDifferent,
+    /// One JSON object per file
+    Json,
+    /// A SARIF 2.1.0 log
+    Sarif,
+    /// Checkstyle XML, like `rustfmt --emit checkstyle`
+    Checkstyle,
+    /// GitHub Actions workflow commands
+    Github,
+    /// One rustc-style JSON diagnostic per chunk, with a suggestion that rustfix can apply
+    Rustfix,
+}
+
+impl Format {
+    /// Parses the argument of a `--message-format` option
+    ///
+    /// Returns `None` for `human`, the default, human-readable format.
+    pub fn parse_message_format(s: &str) -> Result<Option<Self>> {
+        match s {
+            "human" => Ok(None),
+            "json" => Ok(Some(Self::Json)),
+            _ => bail!("unsupported `--message-format` value: {s}"),
+        }
+    }
+
+    /// Parses the argument of an `--emit` option
+    ///
+    /// Returns `None` for `files`, the default, i.e., formatted files are written in place.
+    pub fn parse_emit(s: &str) -> Result<Option<Self>> {
+        match s {
+            "files" => Ok(None),
+            "sarif" => Ok(Some(Self::Sarif)),
+            "checkstyle" => Ok(Some(Self::Checkstyle)),
+            "github" => Ok(Some(Self::Github)),
+            "rustfix" => Ok(Some(Self::Rustfix)),
+            _ => bail!("unsupported `--emit` value: {s}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Status {
+    Unchanged,
+    Reformatted,
+    WouldReformat,
+    SkippedNotFound,
+    Error,
+}
+
+impl Status {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Unchanged => "unchanged",
+            Self::Reformatted => "reformatted",
+            Self::WouldReformat => "would-reformat",
+            Self::SkippedNotFound => "skipped-not-found",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// Writes a report of `outcomes` to standard output
+///
+/// Returns an error if formatting any file failed, or if `--check` was given and any file would be
+/// reformatted.
+pub fn report(opts: &Options, format: Format, outcomes: &[FileOutcome]) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    match format {
+        Format::ListDifferent => {
+            for outcome in outcomes {
+                if status(outcome) == Status::WouldReformat {
+                    writeln!(stdout, "{}", outcome.path.display())?;
+                }
+            }
+        }
+        Format::Json => {
+            for outcome in outcomes {
+                writeln!(stdout, "{}", json(opts, outcome))?;
+            }
+        }
+        Format::Sarif => {
+            let log = serde_json::to_string_pretty(&sarif(outcomes))?;
+            writeln!(stdout, "{log}")?;
+        }
+        Format::Checkstyle => checkstyle(&mut stdout, outcomes)?,
+        Format::Github => {
+            for outcome in outcomes {
+                stdout.write_all_wc(github(outcome).as_bytes())?;
+            }
+        }
+        Format::Rustfix => {
+            for outcome in outcomes {
+                for diagnostic in rustfix(outcome) {
+                    writeln!(stdout, "{diagnostic}")?;
+                }
+            }
+        }
+    }
+    stdout.flush_wc()?;
+
+    let count = |status: Status| {
+        outcomes
+            .iter()
+            .filter(|outcome| self::status(outcome) == status)
+            .count()
+    };
+    let n_errors = count(Status::Error);
+    ensure!(n_errors == 0, "failed to format {n_errors} file(s)");
+    let n_would_reformat = count(Status::WouldReformat);
+    ensure!(
+        n_would_reformat == 0,
+        "{n_would_reformat} file(s) would be reformatted"
+    );
+
+    Ok(())
+}
+
+fn status(outcome: &FileOutcome) -> Status {
+    match &outcome.result {
+        Err(_) => Status::Error,
+        Ok(None) => Status::SkippedNotFound,
+        Ok(Some(_)) if changed_chunks(outcome).next().is_none() => Status::Unchanged,
+        Ok(Some(_)) if outcome.written => Status::Reformatted,
+        Ok(Some(_)) => Status::WouldReformat,
+    }
+}
+
+/// Returns the chunks in `outcome` that changed, or that would change if `--check` was given
+fn changed_chunks(outcome: &FileOutcome) -> impl Iterator<Item = &FormattedChunk> {
+    outcome
+        .result
+        .iter()
+        .flatten()
+        .flat_map(|formatted_source| &formatted_source.formatted_chunks)
+        .filter(|formatted_chunk| formatted_chunk.is_changed())
+}
+
+fn json(opts: &Options, outcome: &FileOutcome) -> Value {
+    // smoelius: Like in `--file-lines` arguments, line ranges are one-based and inclusive.
+    let changed = changed_chunks(outcome)
+        .map(|formatted_chunk| {
+            let lines = &formatted_chunk.chunk.lines;
+            json!([lines.start, lines.end - 1])
+        })
+        .collect::<Vec<_>>();
+    let mut value = json!({
+        "file": outcome.path.to_string_lossy(),
+        "status": status(outcome).as_str(),
+        "changed": changed,
+        "max_width": opts.max_width,
+        "duration": outcome.duration.as_secs_f64(),
+    });
+    if let Err(error) = &outcome.result {
+        value["message"] = Value::String(format!("{error:?}"));
+    }
+    value
+}
+
+/// Returns a SARIF 2.1.0 log with a result for each chunk that changed
+///
+/// Each result has a fix that replaces the chunk with its formatted docs. Errors and files that
+/// were not found are reported as tool execution notifications.
+fn sarif(outcomes: &[FileOutcome]) -> Value {
+    let mut results = Vec::new();
+    let mut notifications = Vec::new();
+    for outcome in outcomes {
+        let uri = uri(&outcome.path);
+        match status(outcome) {
+            Status::Error => {
+                notifications.push(json!({
+                    "level": "error",
+                    "message": { "text": message(outcome) },
+                    "locations": [{ "physicalLocation": { "artifactLocation": { "uri": uri } } }],
+                }));
+            }
+            Status::SkippedNotFound => {
+                notifications.push(json!({
+                    "level": "warning",
+                    "message": { "text": message(outcome) },
+                    "locations": [{ "physicalLocation": { "artifactLocation": { "uri": uri } } }],
+                }));
+            }
+            Status::Unchanged | Status::Reformatted | Status::WouldReformat => {}
+        }
+        let original = outcome
+            .result
+            .as_ref()
+            .ok()
+            .and_then(Option::as_ref)
+            .map_or("", |formatted_source| &formatted_source.original);
+        for formatted_chunk in changed_chunks(outcome) {
+            let lines = &formatted_chunk.chunk.lines;
+            let inserted_content = with_line_endings(original, &formatted_chunk.docs);
+            results.push(json!({
+                "ruleId": RULE_ID,
+                "level": "warning",
+                "message": { "text": MESSAGE },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": uri },
+                        "region": { "startLine": lines.start, "endLine": lines.end - 1 },
+                    },
+                }],
+                "fixes": [{
+                    "description": { "text": "Reformat this doc comment" },
+                    "artifactChanges": [{
+                        "artifactLocation": { "uri": uri },
+                        "replacements": [{
+                            // smoelius: The deleted region extends to the start of the line
+                            // following the chunk, so that it includes the chunk's last newline.
+                            "deletedRegion": {
+                                "startLine": lines.start,
+                                "startColumn": 1,
+                                "endLine": lines.end,
+                                "endColumn": 1,
+                            },
+                            "insertedContent": { "text": inserted_content },
+                        }],
+                    }],
+                }],
+            }));
+        }
+    }
+    let execution_successful = notifications
+        .iter()
+        .all(|notification| notification["level"] != "error");
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": [{
+                        "id": RULE_ID,
+                        "shortDescription": { "text": MESSAGE },
+                    }],
+                },
+            },
+            "invocations": [{
+                "executionSuccessful": execution_successful,
+                "toolExecutionNotifications": notifications,
+            }],
+            "results": results,
+        }],
+    })
+}
+
+/// Converts `path` to a relative URI reference
+fn uri(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Writes a checkstyle XML document with an error element for each chunk that changed
+fn checkstyle(writer: &mut impl Write, outcomes: &[FileOutcome]) -> Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+    writeln!(writer, r#"<checkstyle version="4.3">"#)?;
+    for outcome in outcomes {
+        let errors = match status(outcome) {
+            Status::Error => vec![(1, "error", message(outcome))],
+            Status::SkippedNotFound => vec![(1, "warning", message(outcome))],
+            Status::Unchanged | Status::Reformatted | Status::WouldReformat => {
+                changed_chunks(outcome)
+                    .map(|formatted_chunk| {
+                        (
+                            formatted_chunk.chunk.lines.start,
+                            "warning",
+                            MESSAGE.to_owned(),
+                        )
+                    })
+                    .collect()
+            }
+        };
+        if errors.is_empty() {
+            continue;
+        }
+        let name = xml_escape(&outcome.path.to_string_lossy());
+        writeln!(writer, r#"<file name="{name}">"#)?;
+        for (line, severity, message) in errors {
+            let message = xml_escape(&message);
+            let source = env!("CARGO_PKG_NAME");
+            writeln!(
+                writer,
+                r#"<error line="{line}" severity="{severity}" message="{message}" source="{source}" />"#
+            )?;
+        }
+        writeln!(writer, "</file>")?;
+    }
+    writeln!(writer, "</checkstyle>")?;
+    Ok(())
+}
+
+/// Returns GitHub Actions workflow commands that annotate each chunk in `outcome` that changed
+fn github(outcome: &FileOutcome) -> String {
+    let file = github_escape_property(&outcome.path.to_string_lossy());
+    let title = env!("CARGO_PKG_NAME");
+    match status(outcome) {
+        Status::Error => format!(
+            "::error file={file},title={title}::{}\n",
+            github_escape_data(&message(outcome))
+        ),
+        Status::SkippedNotFound => format!(
+            "::warning file={file},title={title}::{}\n",
+            github_escape_data(&message(outcome))
+        ),
+        Status::Unchanged | Status::Reformatted | Status::WouldReformat => changed_chunks(outcome)
+            .map(|formatted_chunk| {
+                let lines = &formatted_chunk.chunk.lines;
+                format!(
+                    "::warning file={file},line={},endLine={},title={title}::{MESSAGE}\n",
+                    lines.start,
+                    lines.end - 1
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Returns rustc-style JSON diagnostics for `outcome`
+///
+/// Each chunk that changed produces a warning whose child carries a machine-applicable suggestion.
+/// Errors and files that were not found produce diagnostics without spans.
+fn rustfix(outcome: &FileOutcome) -> Vec<Value> {
+    let file_name = outcome.path.to_string_lossy();
+    let level = match status(outcome) {
+        Status::Error => "error",
+        Status::SkippedNotFound => "warning",
+        Status::Unchanged | Status::Reformatted | Status::WouldReformat => {
+            let Ok(Some(formatted_source)) = &outcome.result else {
+                return Vec::new();
+            };
+            let line_starts = line_starts(&formatted_source.original);
+            return changed_chunks(outcome)
+                .map(|formatted_chunk| {
+                    let lines = &formatted_chunk.chunk.lines;
+                    let replacement =
+                        with_line_endings(&formatted_source.original, &formatted_chunk.docs);
+                    let span = |suggested_replacement: Option<&str>| {
+                        rustfix_span(
+                            &file_name,
+                            &formatted_source.original,
+                            &line_starts,
+                            formatted_chunk,
+                            suggested_replacement,
+                        )
+                    };
+                    json!({
+                        "$message_type": "diagnostic",
+                        "message": MESSAGE,
+                        "code": { "code": RULE_ID, "explanation": null },
+                        "level": "warning",
+                        "spans": [span(None)],
+                        "children": [{
+                            "message": "reformat this doc comment",
+                            "code": null,
+                            "level": "help",
+                            "spans": [span(Some(&replacement))],
+                            "children": [],
+                            "rendered": null,
+                        }],
+                        "rendered": format!(
+                            "warning: {MESSAGE}\n --> {file_name}:{}:1\n",
+                            lines.start
+                        ),
+                    })
+                })
+                .collect();
+        }
+    };
+    let message = format!("{file_name}: {}", message(outcome));
+    vec![json!({
+        "$message_type": "diagnostic",
+        "message": message,
+        "code": null,
+        "level": level,
+        "spans": [],
+        "children": [],
+        "rendered": format!("{level}: {message}\n"),
+    })]
+}
+
+/// Returns a rustc-style span covering `formatted_chunk`'s lines, including the last line's
+/// newline
+///
+/// `line_starts` holds the byte offset at which each line of `original` starts.
+fn rustfix_span(
+    file_name: &str,
+    original: &str,
+    line_starts: &[usize],
+    formatted_chunk: &FormattedChunk,
+    suggested_replacement: Option<&str>,
+) -> Value {
+    let lines = &formatted_chunk.chunk.lines;
+    let byte_start = line_starts[lines.start - 1];
+    let byte_end = line_starts
+        .get(lines.end - 1)
+        .copied()
+        .unwrap_or(original.len());
+    let text = original[byte_start..byte_end]
+        .lines()
+        .map(|line| {
+            json!({
+                "text": line,
+                "highlight_start": 1,
+                "highlight_end": line.chars().count() + 1,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "file_name": file_name,
+        "byte_start": byte_start,
+        "byte_end": byte_end,
+        "line_start": lines.start,
+        "line_end": lines.end,
+        "column_start": 1,
+        "column_end": 1,
+        "is_primary": true,
+        "text": text,
+        "label": null,
+        "suggested_replacement": suggested_replacement,
+        "suggestion_applicability": suggested_replacement.map(|_| "MachineApplicable"),
+        "expansion": null,
+    })
+}
+
+/// Returns the byte offset at which each line of `contents` starts
+///
+/// The first line is considered to start after the byte order mark, if any.
+fn line_starts(contents: &str) -> Vec<usize> {
+    let bom_len = if contents.starts_with(BOM) {
+        BOM.len()
+    } else {
+        0
+    };
+    let mut line_starts = vec![bom_len];
+    line_starts.extend(
+        contents
+            .match_indices('\n')
+            .map(|(index, _)| index + 1)
+            .filter(|&index| index < contents.len()),
+    );
+    line_starts
+}
+
+/// Returns a message describing why `outcome` has no formatted chunks
+fn message(outcome: &FileOutcome) -> String {
+    match &outcome.result {
+        Err(error) => format!("{error:?}"),
+        Ok(None) => String::from("file not found"),
+        Ok(Some(_)) => String::new(),
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+        .replace('\n', "&#10;")
+}
+
+/// Escapes the message of a workflow command
+fn github_escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes the value of a workflow command property
+fn github_escape_property(s: &str) -> String {
+    github_escape_data(s)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
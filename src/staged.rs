@@ -0,0 +1,102 @@
+//! Format the doc comments in the files staged in the git index
+//!
+//! Each staged file's blob is read from the index, formatted, and written back to the index. The
+//! file in the working tree is updated only if it has no unstaged changes. Thus, unstaged changes
+//! are never formatted and never committed.
+
+use super::{Options, check_backend, format_source, git, write_atomically};
+use anyhow::{Context, Result, anyhow};
+use elaborate::std::fs::read_to_string_wc;
+use std::path::Path;
+
+/// Mode of a regular file in the git index
+const REGULAR: &str = "100644";
+
+/// Mode of an executable file in the git index
+const EXECUTABLE: &str = "100755";
+
+pub fn format_staged(opts: &Options) -> Result<()> {
+    let toplevel = git::toplevel()?;
+
+    let staged = git::git(
+        &toplevel,
+        &[
+            "diff",
+            "--cached",
+            "--name-only",
+            "--diff-filter=ACMR",
+            "--no-renames",
+            "-z",
+        ],
+    )?;
+
+    let paths = staged
+        .split_terminator('\0')
+        .filter(|path| path.to_lowercase().ends_with(".rs"))
+        .collect::<Vec<_>>();
+
+    let working_tree_paths = paths
+        .iter()
+        .map(|path| toplevel.join(path))
+        .collect::<Vec<_>>();
+    check_backend(opts, &working_tree_paths)?;
+
+    for path in paths {
+        format_staged_file(opts, &toplevel, path)?;
+    }
+
+    Ok(())
+}
+
+/// Formats the blob staged for `path`, which is relative to `toplevel`
+fn format_staged_file(opts: &Options, toplevel: &Path, path: &str) -> Result<()> {
+    let entry = git::git(toplevel, &["ls-files", "--stage", "-z", "--", path])?;
+    let (mode, object) = parse_index_entry(&entry)
+        .ok_or_else(|| anyhow!("failed to parse index entry for `{path}`: {entry:?}"))?;
+
+    // smoelius: Symbolic links and submodules are not formatted.
+    if mode != REGULAR && mode != EXECUTABLE {
+        return Ok(());
+    }
+
+    let contents = git::git(toplevel, &["cat-file", "blob", object])?;
+
+    // smoelius: `path` is relative to `toplevel`, which need not be the current directory. The
+    // backend resolves configuration files and programs relative to the path it is given.
+    let working_tree_path = toplevel.join(path);
+
+    let formatted = format_source(opts.clone(), &working_tree_path, &contents)?.contents;
+
+    if opts.check || formatted == contents {
+        return Ok(());
+    }
+
+    let formatted_object = git::git_with_stdin(
+        toplevel,
+        &["hash-object", "-w", "--stdin", "--path", path],
+        &formatted,
+    )?;
+    let cacheinfo = format!("{mode},{},{path}", formatted_object.trim_end());
+    git::git(toplevel, &["update-index", "--cacheinfo", &cacheinfo])?;
+
+    // smoelius: Update the working tree only if its contents match the blob that was staged.
+    // Otherwise, the file has unstaged changes that writing the formatted blob would discard.
+    if read_to_string_wc(&working_tree_path).ok().as_ref() == Some(&contents) {
+        write_atomically(&working_tree_path, &formatted)
+            .with_context(|| format!("failed to write `{}`", working_tree_path.display()))?;
+    } else {
+        eprintln!("Warning: `{path}` has unstaged changes; only its staged version was formatted");
+    }
+
+    Ok(())
+}
+
+/// Parses the output of `git ls-files --stage -z` for a single file, and returns the file's mode
+/// and object name
+fn parse_index_entry(entry: &str) -> Option<(&str, &str)> {
+    let (metadata, _path) = entry.split_once('\t')?;
+    let mut iter = metadata.split(' ');
+    let mode = iter.next()?;
+    let object = iter.next()?;
+    Some((mode, object))
+}
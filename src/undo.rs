@@ -0,0 +1,139 @@
+//! Record the files a run wrote, so that the run can be undone
+//!
+//! The record is kept in `target/rustdoc-prettier/last-run`, relative to the current directory. It
+//! holds each written file's original contents, and the contents rustdoc-prettier wrote.
+
+use super::{FileOutcome, write_atomically};
+use anyhow::{Context, Result, anyhow, bail};
+use elaborate::std::{
+    env::current_dir_wc,
+    fs::{create_dir_all_wc, read_to_string_wc, remove_dir_all_wc, write_wc},
+    path::PathContext,
+};
+use rewriter::Backup;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+
+const MANIFEST: &str = "manifest.json";
+
+#[derive(Clone)]
+struct Entry {
+    /// The file's canonical path
+    path: PathBuf,
+    original: String,
+    formatted: String,
+}
+
+fn last_run_dir() -> Result<PathBuf> {
+    Ok(current_dir_wc()?.join("target/rustdoc-prettier/last-run"))
+}
+
+/// Records the original and formatted contents of each file in `outcomes` that was written
+///
+/// If no file was written, the record of the previous run is left as is.
+pub fn record(outcomes: &[FileOutcome]) -> Result<()> {
+    let mut entries = Vec::new();
+    for outcome in outcomes {
+        let Ok(Some(formatted_source)) = &outcome.result else {
+            continue;
+        };
+        if !outcome.written || formatted_source.contents == formatted_source.original {
+            continue;
+        }
+        entries.push(Entry {
+            path: outcome.path.canonicalize_wc()?,
+            original: formatted_source.original.clone(),
+            formatted: formatted_source.contents.clone(),
+        });
+    }
+    if entries.is_empty() {
+        return Ok(());
+    }
+    write_manifest(&entries).with_context(|| "failed to record run")
+}
+
+/// Restores the files written by the last run
+///
+/// A file that has changed since the run is not restored, and an error is returned.
+pub fn undo() -> Result<()> {
+    let dir = last_run_dir()?;
+    let manifest = dir.join(MANIFEST);
+    if !manifest.try_exists_wc()? {
+        bail!("found no run to undo in `{}`", dir.display());
+    }
+    let entries = read_manifest(&read_to_string_wc(&manifest)?)?;
+
+    let mut changed = Vec::new();
+    let mut backups = Vec::new();
+    for entry in &entries {
+        #[allow(clippy::disallowed_methods)]
+        let contents = std::fs::read_to_string(&entry.path).ok();
+        if contents.as_ref() != Some(&entry.formatted) {
+            changed.push(entry);
+            continue;
+        }
+        backups.push(Backup::new(&entry.path)?);
+        write_atomically(&entry.path, &entry.original)
+            .with_context(|| format!("failed to restore `{}`", entry.path.display()))?;
+    }
+
+    // smoelius: Each backup restores its file when dropped. So if an error occurred above, any file
+    // that was restored is returned to its formatted state.
+    for mut backup in backups {
+        backup.disable()?;
+    }
+
+    if changed.is_empty() {
+        remove_dir_all_wc(&dir)?;
+        return Ok(());
+    }
+
+    // smoelius: Keep a record of the files that were not restored, so that they can be restored
+    // later, e.g., after the user reverts their changes.
+    let changed = changed.into_iter().cloned().collect::<Vec<_>>();
+    write_manifest(&changed)?;
+    let paths = changed
+        .iter()
+        .map(|entry| format!("\n    {}", entry.path.display()))
+        .collect::<String>();
+    bail!("the following files have changed since the last run and were not restored:{paths}")
+}
+
+fn write_manifest(entries: &[Entry]) -> Result<()> {
+    let dir = last_run_dir()?;
+    create_dir_all_wc(&dir)?;
+    let value = entries
+        .iter()
+        .map(|entry| {
+            json!({
+                "path": entry.path.to_string_lossy(),
+                "original": entry.original,
+                "formatted": entry.formatted,
+            })
+        })
+        .collect::<Value>();
+    write_wc(dir.join(MANIFEST), value.to_string())
+}
+
+fn read_manifest(s: &str) -> Result<Vec<Entry>> {
+    let value = serde_json::from_str::<Value>(s)?;
+    let entries = value
+        .as_array()
+        .ok_or_else(|| anyhow!("manifest is not a list"))?;
+    entries
+        .iter()
+        .map(|entry| {
+            let field = |name: &str| {
+                entry[name]
+                    .as_str()
+                    .map(ToOwned::to_owned)
+                    .ok_or_else(|| anyhow!("manifest entry has no `{name}`: {entry}"))
+            };
+            Ok(Entry {
+                path: PathBuf::from(field("path")?),
+                original: field("original")?,
+                formatted: field("formatted")?,
+            })
+        })
+        .collect()
+}
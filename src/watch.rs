@@ -0,0 +1,174 @@
+//! Reformat source files when they change
+//!
+//! Changes are detected with inotify, so watching is supported only on Linux.
+
+use super::Options;
+use anyhow::Result;
+use std::{collections::HashMap, path::PathBuf};
+
+#[cfg(target_os = "linux")]
+use {
+    super::{CTRLC, format_file},
+    anyhow::Context,
+    elaborate::std::{fs::read_to_string_wc, path::PathContext},
+    inotify::{Inotify, WatchDescriptor, WatchMask},
+    std::{collections::HashSet, io, path::Path, sync::atomic::Ordering, thread, time::Duration},
+};
+
+/// How long to wait for further events after an event is received
+///
+/// Editors can generate several events when saving a file, e.g., by writing to a temporary file
+/// and renaming it. Waiting for a burst of events to end ensures a file is reformatted only once.
+#[cfg(target_os = "linux")]
+const DEBOUNCE_DURATION: Duration = Duration::from_millis(100);
+
+/// How often to check for events and whether Ctrl-C was pressed
+#[cfg(target_os = "linux")]
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Size of the buffer into which events are read
+///
+/// The buffer holds at least one event with a name of `NAME_MAX` bytes. Otherwise, reading events
+/// could fail.
+#[cfg(target_os = "linux")]
+const BUF_SIZE: usize = 4096;
+
+#[cfg(target_os = "linux")]
+pub struct Watcher {
+    inotify: Inotify,
+    /// Maps watch descriptors to the directories they watch
+    dirs: HashMap<WatchDescriptor, PathBuf>,
+    /// The files to reformat when they change
+    paths: HashSet<PathBuf>,
+}
+
+#[cfg(target_os = "linux")]
+impl Watcher {
+    /// Starts watching `paths` for changes
+    ///
+    /// Changes are observed from the time this function returns. Calling it before the initial
+    /// formatting pass ensures that a change made during that pass is not missed.
+    pub fn new(paths: &[PathBuf]) -> Result<Self> {
+        // smoelius: Watch the files' parent directories rather than the files themselves. Editors
+        // often save a file by renaming another over it, and a watch on the original file would not
+        // observe that.
+        let inotify = Inotify::init().with_context(|| "failed to initialize inotify")?;
+        let mut dirs = HashMap::new();
+        for path in paths {
+            let dir = path.parent_wc()?;
+            // smoelius: An empty parent refers to the current directory, but cannot be watched as
+            // such.
+            let watched_dir = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            };
+            let wd = inotify
+                .watches()
+                .add(watched_dir, WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO)
+                .with_context(|| format!("failed to watch `{}`", watched_dir.display()))?;
+            dirs.insert(wd, dir.to_path_buf());
+        }
+        Ok(Self {
+            inotify,
+            dirs,
+            paths: paths.iter().cloned().collect(),
+        })
+    }
+
+    /// Reformats each watched file when it changes, until Ctrl-C is pressed
+    ///
+    /// `formatted_contents` maps paths to their contents following the initial formatting pass. A
+    /// file whose contents match those last written by rustdoc-prettier is not reformatted. This
+    /// prevents rustdoc-prettier's own writes from triggering reformatting.
+    pub fn watch(
+        mut self,
+        opts: &Options,
+        mut formatted_contents: HashMap<PathBuf, String>,
+    ) -> Result<()> {
+        eprintln!("Watching for changes. Press Ctrl-C to exit.");
+
+        loop {
+            if CTRLC.load(Ordering::SeqCst) {
+                return Ok(());
+            }
+
+            let mut changed_paths = self.read_events()?;
+            if changed_paths.is_empty() {
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            loop {
+                thread::sleep(DEBOUNCE_DURATION);
+                let more_changed_paths = self.read_events()?;
+                if more_changed_paths.is_empty() {
+                    break;
+                }
+                changed_paths.extend(more_changed_paths);
+            }
+
+            for path in changed_paths {
+                if !self.paths.contains(&path) {
+                    continue;
+                }
+                // smoelius: The file could have been removed. In that case, there is nothing to do.
+                let Ok(contents) = read_to_string_wc(&path) else {
+                    continue;
+                };
+                if formatted_contents.get(&path) == Some(&contents) {
+                    continue;
+                }
+                match format_file(opts.clone(), &path) {
+                    Ok(Some(formatted_source)) => {
+                        formatted_contents.insert(path, formatted_source.contents);
+                    }
+                    Ok(None) => {}
+                    // smoelius: A file being edited may fail to format, e.g., because it is
+                    // incomplete. Report the error and keep watching.
+                    Err(error) => eprintln!("Error: {error:?}"),
+                }
+            }
+        }
+    }
+
+    /// Reads the events that are ready without waiting, and returns the paths of the files they
+    /// concern
+    fn read_events(&mut self) -> Result<Vec<PathBuf>> {
+        let mut buf = [0; BUF_SIZE];
+        let mut paths = Vec::new();
+        loop {
+            let events = match self.inotify.read_events(&mut buf) {
+                Ok(events) => events,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(paths),
+                Err(error) => {
+                    return Err(error).with_context(|| "failed to read inotify events");
+                }
+            };
+            for event in events {
+                if let Some(dir) = self.dirs.get(&event.wd)
+                    && let Some(name) = event.name
+                {
+                    paths.push(dir.join(name));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub enum Watcher {}
+
+#[cfg(not(target_os = "linux"))]
+impl Watcher {
+    pub fn new(_paths: &[PathBuf]) -> Result<Self> {
+        anyhow::bail!("`--watch` is supported only on Linux")
+    }
+
+    pub fn watch(
+        self,
+        _opts: &Options,
+        _formatted_contents: HashMap<PathBuf, String>,
+    ) -> Result<()> {
+        match self {}
+    }
+}
@@ -0,0 +1,79 @@
+use elaborate::std::fs::read_dir_wc;
+
+mod util;
+use util::{FORMATTED, Fixture, UNFORMATTED};
+
+#[test]
+fn crlf_is_preserved() {
+    let source = format!("{UNFORMATTED}\n/// Formatted\nfn bar() {{}}\n").replace('\n', "\r\n");
+    let fixture = Fixture::new(&[("lib.rs", &source)]);
+
+    fixture.command().arg("lib.rs").assert().success();
+
+    assert_eq!(
+        format!("{FORMATTED}\n/// Formatted\nfn bar() {{}}\n").replace('\n', "\r\n"),
+        fixture.read("lib.rs")
+    );
+}
+
+#[test]
+fn bom_is_preserved() {
+    let fixture = Fixture::new(&[("lib.rs", &format!("\u{feff}{UNFORMATTED}"))]);
+
+    fixture.command().arg("lib.rs").assert().success();
+
+    assert_eq!(format!("\u{feff}{FORMATTED}"), fixture.read("lib.rs"));
+}
+
+#[test]
+fn temporary_file_is_removed() {
+    let fixture = Fixture::new(&[("lib.rs", UNFORMATTED)]);
+
+    fixture.command().arg("lib.rs").assert().success();
+
+    let tmp_file_names = read_dir_wc(fixture.path("."))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|file_name| file_name.contains(".rustdoc-prettier-"))
+        .collect::<Vec<_>>();
+    assert!(tmp_file_names.is_empty(), "{tmp_file_names:?}");
+}
+
+#[cfg(unix)]
+#[test]
+fn permissions_are_preserved() {
+    use elaborate::std::fs::{metadata_wc, set_permissions_wc};
+    use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+
+    let fixture = Fixture::new(&[("lib.rs", UNFORMATTED)]);
+    let path = fixture.path("lib.rs");
+    set_permissions_wc(&path, Permissions::from_mode(0o755)).unwrap();
+
+    fixture.command().arg("lib.rs").assert().success();
+
+    assert_eq!(
+        0o755,
+        metadata_wc(&path).unwrap().permissions().mode() & 0o777
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn symlink_target_is_written() {
+    use elaborate::std::fs::symlink_metadata_wc;
+    use std::os::unix::fs::symlink;
+
+    let fixture = Fixture::new(&[("target.rs", UNFORMATTED)]);
+    #[allow(clippy::disallowed_methods)]
+    symlink("target.rs", fixture.path("link.rs")).unwrap();
+
+    fixture.command().arg("link.rs").assert().success();
+
+    assert!(
+        symlink_metadata_wc(fixture.path("link.rs"))
+            .unwrap()
+            .file_type()
+            .is_symlink()
+    );
+    assert_eq!(FORMATTED, fixture.read("target.rs"));
+}
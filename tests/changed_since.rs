@@ -0,0 +1,133 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use elaborate::std::fs::{read_to_string_wc, write_wc};
+use tempfile::tempdir;
+
+mod util;
+use util::git;
+
+const SOURCE: &str = "\
+///  Needs formatting
+fn foo() {}
+
+///  Needs formatting
+fn bar() {}
+";
+
+#[test]
+fn changed_since() {
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("a.rs"), SOURCE).unwrap();
+    write_wc(tempdir.path().join("b.rs"), SOURCE).unwrap();
+    git(tempdir.path(), &["init", "--quiet"]);
+    git(tempdir.path(), &["add", "."]);
+    git(
+        tempdir.path(),
+        &["commit", "--quiet", "--message", "Initial commit"],
+    );
+
+    let modified = SOURCE.replace(
+        "///  Needs formatting\nfn bar",
+        "///  Still needs formatting\nfn bar",
+    );
+    write_wc(tempdir.path().join("a.rs"), &modified).unwrap();
+    write_wc(tempdir.path().join("c.rs"), SOURCE).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["--changed-since", "HEAD"]);
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    // smoelius: Only the chunk that intersects the modified line is formatted.
+    let contents = read_to_string_wc(tempdir.path().join("a.rs")).unwrap();
+    assert_eq!(
+        "\
+///  Needs formatting
+fn foo() {}
+
+/// Still needs formatting
+fn bar() {}
+",
+        contents
+    );
+
+    // smoelius: Unmodified files are not formatted.
+    let contents = read_to_string_wc(tempdir.path().join("b.rs")).unwrap();
+    assert_eq!(SOURCE, contents);
+
+    // smoelius: Untracked files are formatted in their entirety.
+    let contents = read_to_string_wc(tempdir.path().join("c.rs")).unwrap();
+    assert_eq!(SOURCE.replace("///  ", "/// "), contents);
+}
+
+#[test]
+fn changed_since_noprefix() {
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("a.rs"), SOURCE).unwrap();
+    git(tempdir.path(), &["init", "--quiet"]);
+    git(tempdir.path(), &["config", "diff.noprefix", "true"]);
+    git(tempdir.path(), &["add", "."]);
+    git(
+        tempdir.path(),
+        &["commit", "--quiet", "--message", "Initial commit"],
+    );
+
+    let modified = SOURCE.replace(
+        "///  Needs formatting\nfn bar",
+        "///  Still needs formatting\nfn bar",
+    );
+    write_wc(tempdir.path().join("a.rs"), &modified).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["--changed-since", "HEAD"]);
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    // smoelius: The modified chunk is found even though `git diff` would print paths without
+    // prefixes by default.
+    let contents = read_to_string_wc(tempdir.path().join("a.rs")).unwrap();
+    assert_eq!(
+        "\
+///  Needs formatting
+fn foo() {}
+
+/// Still needs formatting
+fn bar() {}
+",
+        contents
+    );
+}
+
+#[test]
+fn changed_since_added_line_like_header() {
+    const SOURCE: &str = "\
+const S: &str = \"
+\";
+
+///  Needs formatting
+fn foo() {}
+";
+
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("a.rs"), SOURCE).unwrap();
+    git(tempdir.path(), &["init", "--quiet"]);
+    git(tempdir.path(), &["add", "."]);
+    git(
+        tempdir.path(),
+        &["commit", "--quiet", "--message", "Initial commit"],
+    );
+
+    // smoelius: The added line `++ b/b.rs` appears in the diff as `+++ b/b.rs`, which looks like
+    // the header of a file named `b.rs`.
+    let modified = SOURCE
+        .replace("\"\n\"", "\"\n++ b/b.rs\n\"")
+        .replace("///  Needs", "///  Still needs");
+    write_wc(tempdir.path().join("a.rs"), &modified).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["--changed-since", "HEAD"]);
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    let contents = read_to_string_wc(tempdir.path().join("a.rs")).unwrap();
+    assert_eq!(modified.replace("///  ", "/// "), contents);
+}
@@ -0,0 +1,31 @@
+use predicates::prelude::*;
+
+mod util;
+use util::{FORMATTED, Fixture, UNFORMATTED};
+
+#[test]
+fn backend() {
+    let fixture = Fixture::new(&[
+        ("rustdoc-prettier.toml", "backend = \"prettier\"\n"),
+        ("lib.rs", UNFORMATTED),
+    ]);
+
+    fixture.command().arg("lib.rs").assert().success();
+
+    assert_eq!(FORMATTED, fixture.read("lib.rs"));
+}
+
+#[test]
+fn unknown_backend() {
+    let fixture = Fixture::new(&[
+        (".rustdoc-prettier.toml", "backend = \"unknown\"\n"),
+        ("lib.rs", UNFORMATTED),
+    ]);
+
+    fixture
+        .command()
+        .arg("lib.rs")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown backend: unknown"));
+}
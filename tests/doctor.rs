@@ -0,0 +1,87 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use elaborate::std::fs::{create_dir_all_wc, write_wc};
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+mod util;
+
+/// A stand-in for `prettier` that finds a configuration file named `.prettierrc` and ignores no
+/// files
+const PRETTIER: &str = "\
+#!/bin/sh
+case \"$1\" in
+    --version) echo 3.0.0 ;;
+    --find-config-path) echo .prettierrc ;;
+    --file-info) echo '{ \"ignored\": false, \"inferredParser\": \"markdown\" }' ;;
+    *) cat ;;
+esac
+";
+
+#[test]
+fn doctor() {
+    let tempdir = tempdir().unwrap();
+    util::write_script(&tempdir.path().join("prettier"), PRETTIER);
+    create_dir_all_wc(tempdir.path().join("src")).unwrap();
+    write_wc(tempdir.path().join("src/lib.rs"), "").unwrap();
+    write_wc(tempdir.path().join("rustfmt.toml"), "max_width = 100\n").unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["doctor", "src/lib.rs", "--prettier-path", "./prettier"]);
+    command.current_dir(&tempdir);
+    command.assert().success().stdout(
+        predicate::str::contains(
+            "prettier: ./prettier (found via `--prettier-path`), version 3.0.0\n",
+        )
+        .and(predicate::str::contains(
+            "`///` width: 96 at indent 0, 92 at indent 4, 88 at indent 8\n",
+        ))
+        .and(predicate::str::contains("prettier config: .prettierrc\n")),
+    );
+}
+
+#[test]
+fn doctor_advice() {
+    let tempdir = tempdir().unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["doctor", "--prettier-path", "./nonexistent"]);
+    command.current_dir(&tempdir);
+    command
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "prettier: `./nonexistent` (found via `--prettier-path`) failed to run",
+        ))
+        .stdout(predicate::str::contains(
+            "    advice: install prettier, e.g., with `npm install --save-dev prettier`",
+        ))
+        .stderr(predicate::str::contains("found 1 problem"));
+}
+
+#[test]
+fn doctor_missing_plugin() {
+    let tempdir = tempdir().unwrap();
+    util::write_script(&tempdir.path().join("prettier"), PRETTIER);
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args([
+        "doctor",
+        "--prettier-path",
+        "./prettier",
+        "--plugin",
+        "prettier-plugin-nonexistent",
+    ]);
+    command.current_dir(&tempdir);
+    command
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "plugin `prettier-plugin-nonexistent`: failed to load",
+        ))
+        .stdout(predicate::str::contains(
+            "    advice: install the plugin, e.g., with `npm install --save-dev \
+             prettier-plugin-nonexistent`",
+        ));
+}
@@ -0,0 +1,129 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use elaborate::std::fs::write_wc;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+#[cfg(unix)]
+use {
+    assert_cmd::Command,
+    elaborate::std::{
+        env::{join_paths_wc, var_os_wc},
+        fs::{create_dir_all_wc, read_to_string_wc},
+        path::PathContext,
+    },
+    std::{env::split_paths, path::Path},
+};
+
+mod util;
+
+#[test]
+fn dprint_requires_config_file() {
+    let tempdir = tempdir().unwrap();
+    write_wc(
+        tempdir.path().join("lib.rs"),
+        "/// Formatted\nfn foo() {}\n",
+    )
+    .unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["--backend", "dprint", "lib.rs"]);
+    command.current_dir(&tempdir);
+    command.assert().failure().stderr(predicate::str::contains(
+        "found no dprint configuration file",
+    ));
+}
+
+// smoelius: dprint's markdown plugin is downloaded when first used. To avoid depending on the
+// network, these tests use a stand-in for `dprint` that records its arguments and the contents of
+// the configuration file it is given.
+#[cfg(unix)]
+const DPRINT: &str = "\
+#!/bin/sh
+if [ \"$1\" = --version ]; then
+    echo 'dprint 0.0.0'
+    exit
+fi
+echo \"$@\" >> args.txt
+if [ \"$2\" = --config ]; then
+    cat \"$3\" >> config.txt
+fi
+cat
+";
+
+#[cfg(unix)]
+#[test]
+fn dprint_line_width() {
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("dprint.json"), "{}\n").unwrap();
+    write_wc(
+        tempdir.path().join("lib.rs"),
+        "/// Formatted\nfn foo() {}\n",
+    )
+    .unwrap();
+
+    let mut command = dprint_command(tempdir.path());
+    command.args(["--backend", "dprint", "--max-width", "80", "lib.rs"]);
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    let args = read_to_string_wc(tempdir.path().join("args.txt")).unwrap();
+    let args = args.split_whitespace().collect::<Vec<_>>();
+    let [fmt, "--config", config, "--stdin", stdin] = args.as_slice() else {
+        panic!("unexpected arguments: {args:?}");
+    };
+    assert_eq!("fmt", *fmt);
+    assert_eq!("lib.rs.rustdoc.md", *stdin);
+
+    // smoelius: The generated configuration file is written to a temporary directory, not the
+    // current directory, and is removed once dprint finishes.
+    assert!(!Path::new(config).starts_with(tempdir.path()), "{config}");
+    assert!(!Path::new(config).exists(), "{config}");
+    assert!(!tempdir.path().join("target").exists());
+
+    let config = read_to_string_wc(tempdir.path().join("config.txt")).unwrap();
+    assert!(config.contains(r#""lineWidth":76"#), "{config}");
+    assert!(config.contains(r#""textWrap":"always""#), "{config}");
+}
+
+#[cfg(unix)]
+#[test]
+fn dprint_config_file_nearest_source_file() {
+    let tempdir = tempdir().unwrap();
+    let dir = tempdir.path().join("dir");
+    create_dir_all_wc(&dir).unwrap();
+    write_wc(dir.join("dprint.json"), "{}\n").unwrap();
+    write_wc(dir.join("lib.rs"), "/// Formatted\nfn foo() {}\n").unwrap();
+
+    let mut command = dprint_command(tempdir.path());
+    command.args(["--backend", "dprint", "--max-width", "80", "dir/lib.rs"]);
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    // smoelius: The configuration file is found from the source file's directory, which is
+    // canonical, e.g., on macOS, `/private/var` rather than `/var`.
+    let config = read_to_string_wc(tempdir.path().join("config.txt")).unwrap();
+    let extends = dir.canonicalize_wc().unwrap().join("dprint.json");
+    assert!(
+        config.contains(&format!(r#""extends":"{}""#, extends.display())),
+        "{config}"
+    );
+}
+
+/// Returns a command that runs rustdoc-prettier with a stand-in for `dprint` written to `dir`
+#[cfg(unix)]
+fn dprint_command(dir: &Path) -> Command {
+    let bin = dir.join("bin");
+    create_dir_all_wc(&bin).unwrap();
+    util::write_script(&bin.join("dprint"), DPRINT);
+
+    let path = join_paths_wc(
+        [bin]
+            .into_iter()
+            .chain(split_paths(&var_os_wc("PATH").unwrap_or_default())),
+    )
+    .unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.env("PATH", path);
+    command
+}
@@ -0,0 +1,200 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use elaborate::std::fs::{read_to_string_wc, write_wc};
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+const SOURCE: &str = "\
+/// Formatted
+fn foo() {}
+
+///  Needs
+///  formatting
+fn bar() {}
+";
+
+#[test]
+fn emit_sarif() {
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("lib.rs"), SOURCE).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["lib.rs", "--check", "--emit", "sarif"]);
+    command.current_dir(&tempdir);
+    let assert = command.assert().failure();
+
+    let log = serde_json::from_slice::<Value>(&assert.get_output().stdout).unwrap();
+    assert_eq!("2.1.0", log["version"]);
+    let run = &log["runs"][0];
+    assert_eq!(true, run["invocations"][0]["executionSuccessful"]);
+    assert_eq!(
+        json!([{
+            "ruleId": "unformatted-doc-comment",
+            "level": "warning",
+            "message": { "text": "doc comment is not formatted" },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": "lib.rs" },
+                    "region": { "startLine": 4, "endLine": 5 },
+                },
+            }],
+            "fixes": [{
+                "description": { "text": "Reformat this doc comment" },
+                "artifactChanges": [{
+                    "artifactLocation": { "uri": "lib.rs" },
+                    "replacements": [{
+                        "deletedRegion": {
+                            "startLine": 4,
+                            "startColumn": 1,
+                            "endLine": 6,
+                            "endColumn": 1,
+                        },
+                        "insertedContent": { "text": "/// Needs formatting\n" },
+                    }],
+                }],
+            }],
+        }]),
+        run["results"]
+    );
+
+    assert_eq!(
+        SOURCE,
+        read_to_string_wc(tempdir.path().join("lib.rs")).unwrap()
+    );
+}
+
+#[test]
+fn emit_sarif_crlf() {
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("lib.rs"), SOURCE.replace('\n', "\r\n")).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["lib.rs", "--check", "--emit", "sarif"]);
+    command.current_dir(&tempdir);
+    let assert = command.assert().failure();
+
+    let log = serde_json::from_slice::<Value>(&assert.get_output().stdout).unwrap();
+    let replacement =
+        &log["runs"][0]["results"][0]["fixes"][0]["artifactChanges"][0]["replacements"][0];
+    assert_eq!(
+        json!({ "text": "/// Needs formatting\r\n" }),
+        replacement["insertedContent"]
+    );
+}
+
+#[test]
+fn emit_checkstyle() {
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("lib.rs"), SOURCE).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["lib.rs", "--check", "--emit=checkstyle"]);
+    command.current_dir(&tempdir);
+    command.assert().failure().stdout(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<checkstyle version="4.3">
+<file name="lib.rs">
+<error line="4" severity="warning" message="doc comment is not formatted" source="rustdoc-prettier" />
+</file>
+</checkstyle>
+"#,
+    );
+}
+
+#[test]
+fn emit_github() {
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("lib.rs"), SOURCE).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["lib.rs", "--check", "--emit=github"]);
+    command.current_dir(&tempdir);
+    command.assert().failure().stdout(
+        "::warning file=lib.rs,line=4,endLine=5,title=rustdoc-prettier::doc comment is not \
+         formatted\n",
+    );
+}
+
+#[test]
+fn emit_rustfix() {
+    assert_emit_rustfix("\n");
+}
+
+#[test]
+fn emit_rustfix_crlf() {
+    assert_emit_rustfix("\r\n");
+}
+
+fn assert_emit_rustfix(line_ending: &str) {
+    let source = SOURCE.replace('\n', line_ending);
+
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("lib.rs"), &source).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["lib.rs", "--check", "--emit", "rustfix"]);
+    command.current_dir(&tempdir);
+    let assert = command.assert().failure();
+
+    let stdout = str::from_utf8(&assert.get_output().stdout).unwrap();
+    let diagnostics = stdout
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(1, diagnostics.len());
+    let diagnostic = &diagnostics[0];
+    assert_eq!("warning", diagnostic["level"]);
+    assert_eq!("doc comment is not formatted", diagnostic["message"]);
+
+    // smoelius: Applying the suggestion's byte-span replacement yields the formatted source.
+    let span = &diagnostic["children"][0]["spans"][0];
+    assert_eq!("MachineApplicable", span["suggestion_applicability"]);
+    let byte_start = usize::try_from(span["byte_start"].as_u64().unwrap()).unwrap();
+    let byte_end = usize::try_from(span["byte_end"].as_u64().unwrap()).unwrap();
+    let replacement = span["suggested_replacement"].as_str().unwrap();
+    let fixed = format!(
+        "{}{replacement}{}",
+        &source[..byte_start],
+        &source[byte_end..]
+    );
+    assert_eq!(
+        SOURCE
+            .replace("///  Needs\n///  formatting", "/// Needs formatting")
+            .replace('\n', line_ending),
+        fixed
+    );
+}
+
+#[test]
+fn emit_sarif_does_not_write_files() {
+    assert_emit_does_not_write_files("sarif");
+}
+
+#[test]
+fn emit_checkstyle_does_not_write_files() {
+    assert_emit_does_not_write_files("checkstyle");
+}
+
+#[test]
+fn emit_github_does_not_write_files() {
+    assert_emit_does_not_write_files("github");
+}
+
+#[test]
+fn emit_rustfix_does_not_write_files() {
+    assert_emit_does_not_write_files("rustfix");
+}
+
+fn assert_emit_does_not_write_files(format: &str) {
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("lib.rs"), SOURCE).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["lib.rs", "--emit", format]);
+    command.current_dir(&tempdir);
+    command.assert().failure();
+
+    assert_eq!(
+        SOURCE,
+        read_to_string_wc(tempdir.path().join("lib.rs")).unwrap()
+    );
+}
@@ -0,0 +1,44 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use elaborate::std::fs::{read_to_string_wc, write_wc};
+use tempfile::tempdir;
+
+const SOURCE: &str = "\
+///  Needs formatting
+fn foo() {}
+
+///  Needs formatting
+fn bar() {}
+";
+
+#[test]
+fn file_lines() {
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("a.rs"), SOURCE).unwrap();
+    write_wc(tempdir.path().join("b.rs"), SOURCE).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args([
+        "a.rs",
+        "b.rs",
+        "--file-lines",
+        r#"[{"file":"a.rs","range":[3,4]}]"#,
+    ]);
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    let contents = read_to_string_wc(tempdir.path().join("a.rs")).unwrap();
+    assert_eq!(
+        "\
+///  Needs formatting
+fn foo() {}
+
+/// Needs formatting
+fn bar() {}
+",
+        contents
+    );
+
+    // smoelius: Files not mentioned in the `--file-lines` argument are not formatted.
+    let contents = read_to_string_wc(tempdir.path().join("b.rs")).unwrap();
+    assert_eq!(SOURCE, contents);
+}
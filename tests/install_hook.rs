@@ -0,0 +1,174 @@
+use assert_cmd::cargo::{cargo_bin, cargo_bin_cmd};
+use elaborate::std::{
+    env::{join_paths_wc, var_os_wc},
+    fs::{read_to_string_wc, write_wc},
+    path::PathContext,
+    process::CommandContext,
+};
+use predicates::prelude::*;
+use std::{env::split_paths, iter, path::Path, process::ExitStatus};
+use tempfile::tempdir;
+
+mod util;
+use util::{git, git_command};
+
+const SOURCE: &str = "\
+///  Needs formatting
+fn foo() {}
+";
+
+#[cfg_attr(windows, ignore = "pre-commit hooks are shell scripts")]
+#[test]
+fn install_hook() {
+    let tempdir = tempdir().unwrap();
+    git(tempdir.path(), &["init", "--quiet"]);
+    let hook = tempdir.path().join(".git/hooks/pre-commit");
+    write_wc(&hook, "#!/bin/sh\n\necho 'Existing hook'\n").unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["install-hook", "--check"]);
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    assert_eq!(
+        "\
+#!/bin/sh
+
+echo 'Existing hook'
+
+# rustdoc-prettier: begin
+rustdoc-prettier --staged --check || exit 1
+# rustdoc-prettier: end
+",
+        read_to_string_wc(&hook).unwrap()
+    );
+
+    write_wc(tempdir.path().join("a.rs"), SOURCE).unwrap();
+    git(tempdir.path(), &["add", "a.rs"]);
+
+    // smoelius: In check mode, the hook rejects the commit.
+    let status = commit(tempdir.path());
+    assert!(!status.success());
+
+    // smoelius: Installing the hook again replaces rustdoc-prettier's commands.
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.arg("install-hook");
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    assert_eq!(
+        "\
+#!/bin/sh
+
+echo 'Existing hook'
+
+# rustdoc-prettier: begin
+rustdoc-prettier --staged || exit 1
+# rustdoc-prettier: end
+",
+        read_to_string_wc(&hook).unwrap()
+    );
+
+    // smoelius: In fix-and-restage mode, the hook formats the staged file and the commit succeeds.
+    let status = commit(tempdir.path());
+    assert!(status.success());
+    assert_eq!(
+        SOURCE.replace("///  ", "/// "),
+        read_to_string_wc(tempdir.path().join("a.rs")).unwrap()
+    );
+}
+
+#[cfg_attr(windows, ignore = "pre-commit hooks are shell scripts")]
+#[test]
+fn install_hook_before_trailing_exit() {
+    let tempdir = tempdir().unwrap();
+    git(tempdir.path(), &["init", "--quiet"]);
+    let hook = tempdir.path().join(".git/hooks/pre-commit");
+    write_wc(&hook, "#!/bin/sh\n\necho 'Existing hook'\nexit 0\n").unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["install-hook", "--check"]);
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    assert_eq!(
+        "\
+#!/bin/sh
+
+echo 'Existing hook'
+
+# rustdoc-prettier: begin
+rustdoc-prettier --staged --check || exit 1
+# rustdoc-prettier: end
+
+exit 0
+",
+        read_to_string_wc(&hook).unwrap()
+    );
+
+    // smoelius: The hook's commands run, so the commit is rejected.
+    write_wc(tempdir.path().join("a.rs"), SOURCE).unwrap();
+    git(tempdir.path(), &["add", "a.rs"]);
+    let status = commit(tempdir.path());
+    assert!(!status.success());
+}
+
+#[cfg_attr(windows, ignore = "pre-commit hooks are shell scripts")]
+#[test]
+fn install_hook_shebang_with_option() {
+    let tempdir = tempdir().unwrap();
+    git(tempdir.path(), &["init", "--quiet"]);
+    let hook = tempdir.path().join(".git/hooks/pre-commit");
+    write_wc(&hook, "#!/bin/sh -e\n\necho 'Existing hook'\n").unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["install-hook", "--ignore-prettier-version"]);
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    assert_eq!(
+        "\
+#!/bin/sh -e
+
+echo 'Existing hook'
+
+# rustdoc-prettier: begin
+rustdoc-prettier --staged --ignore-prettier-version || exit 1
+# rustdoc-prettier: end
+",
+        read_to_string_wc(&hook).unwrap()
+    );
+}
+
+#[test]
+fn install_hook_rejects_inapplicable_option() {
+    let tempdir = tempdir().unwrap();
+    git(tempdir.path(), &["init", "--quiet"]);
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["install-hook", "--no-transaction"]);
+    command.current_dir(&tempdir);
+    command.assert().failure().stderr(predicate::str::contains(
+        "`install-hook` accepts only `--max-width`",
+    ));
+
+    assert!(
+        !tempdir
+            .path()
+            .join(".git/hooks/pre-commit")
+            .try_exists_wc()
+            .unwrap()
+    );
+}
+
+/// Commits with rustdoc-prettier in the `PATH`
+fn commit(dir: &Path) -> ExitStatus {
+    let bin_dir = cargo_bin!("rustdoc-prettier").parent_wc().unwrap();
+    let path = var_os_wc("PATH").unwrap_or_default();
+    let paths = iter::once(bin_dir.to_path_buf()).chain(split_paths(&path));
+    git_command(dir)
+        .args(["commit", "--quiet", "--message", "Initial commit"])
+        .env("PATH", join_paths_wc(paths).unwrap())
+        .status_wc()
+        .unwrap()
+}
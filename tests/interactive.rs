@@ -0,0 +1,101 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use elaborate::std::fs::{read_to_string_wc, write_wc};
+use tempfile::tempdir;
+
+const SOURCE: &str = "\
+///  First
+fn foo() {}
+
+///  Second
+fn bar() {}
+
+///  Third
+fn baz() {}
+";
+
+#[cfg_attr(windows, ignore = "the editor command uses Unix shell quoting")]
+#[test]
+fn interactive() {
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("lib.rs"), SOURCE).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["lib.rs", "--interactive"]);
+    command.current_dir(&tempdir);
+    command.env_remove("VISUAL");
+    command.env("EDITOR", "perl -pi -e s/Third/Edited/");
+    command.write_stdin("n\n?\ny\ne\n");
+    let assert = command.assert().success();
+
+    assert_eq!(
+        "\
+///  First
+fn foo() {}
+
+/// Second
+fn bar() {}
+
+/// Edited
+fn baz() {}
+",
+        read_to_string_wc(tempdir.path().join("lib.rs")).unwrap()
+    );
+
+    let stderr = str::from_utf8(&assert.get_output().stderr).unwrap();
+    assert!(
+        stderr.starts_with(
+            "\
+lib.rs:1-1
+-///  First
++/// First
+ fn foo() {}
+ 
+Accept this change [y,n,e,a,q,?]? lib.rs:4-4
+ fn foo() {}
+ 
+-///  Second
++/// Second
+ fn bar() {}
+ 
+Accept this change [y,n,e,a,q,?]? y - accept this change
+"
+        ),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn interactive_accept_all() {
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("lib.rs"), SOURCE).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["lib.rs", "--interactive"]);
+    command.current_dir(&tempdir);
+    command.write_stdin("n\na\n");
+    command.assert().success();
+
+    assert_eq!(
+        SOURCE
+            .replace("///  Second", "/// Second")
+            .replace("///  Third", "/// Third"),
+        read_to_string_wc(tempdir.path().join("lib.rs")).unwrap()
+    );
+}
+
+#[test]
+fn interactive_quit_at_end_of_input() {
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("lib.rs"), SOURCE).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["lib.rs", "--interactive"]);
+    command.current_dir(&tempdir);
+    command.write_stdin("y\n");
+    command.assert().success();
+
+    assert_eq!(
+        SOURCE.replace("///  First", "/// First"),
+        read_to_string_wc(tempdir.path().join("lib.rs")).unwrap()
+    );
+}
@@ -0,0 +1,37 @@
+use elaborate::std::fs::{FileContext, MetadataContext, OpenOptionsContext, metadata_wc};
+use std::{
+    fs::OpenOptions,
+    time::{Duration, SystemTime},
+};
+
+mod util;
+use util::{FORMATTED, Fixture, UNFORMATTED};
+
+#[test]
+fn list_different() {
+    let fixture = Fixture::new(&[("a.rs", UNFORMATTED), ("b.rs", FORMATTED)]);
+
+    fixture
+        .command()
+        .args(["a.rs", "b.rs", "--list-different"])
+        .assert()
+        .failure()
+        .stdout("a.rs\n");
+
+    assert_eq!(UNFORMATTED, fixture.read("a.rs"));
+}
+
+#[test]
+fn unchanged_file_is_not_written() {
+    let fixture = Fixture::new(&[("lib.rs", FORMATTED)]);
+    let path = fixture.path("lib.rs");
+
+    let modified = SystemTime::UNIX_EPOCH + Duration::from_hours(24);
+    let file = OpenOptions::new().write(true).open_wc(&path).unwrap();
+    file.set_modified_wc(modified).unwrap();
+    drop(file);
+
+    fixture.command().arg("lib.rs").assert().success();
+
+    assert_eq!(modified, metadata_wc(&path).unwrap().modified_wc().unwrap());
+}
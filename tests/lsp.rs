@@ -0,0 +1,246 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use serde_json::{Value, json};
+use tempfile::{TempDir, tempdir};
+
+const SOURCE: &str = "\
+///  Needs formatting
+fn foo() {}
+
+/// Does not need formatting
+fn bar() {}
+";
+
+#[cfg_attr(windows, ignore = "testing on Unix-like platforms is sufficient")]
+#[test]
+fn lsp() {
+    let tempdir = tempdir().unwrap();
+    let uri = format!("file://{}/lib.rs", tempdir.path().display());
+
+    let requests = [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": SOURCE },
+            },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "textDocument/rangeFormatting",
+            "params": {
+                "textDocument": { "uri": uri },
+                "range": range(0, 4),
+                "options": { "tabSize": 4, "insertSpaces": true },
+            },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "textDocument/codeAction",
+            "params": {
+                "textDocument": { "uri": uri },
+                "range": range(0, 0),
+                "context": { "diagnostics": [] },
+            },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "textDocument/codeAction",
+            "params": {
+                "textDocument": { "uri": uri },
+                "range": range(3, 3),
+                "context": { "diagnostics": [] },
+            },
+        }),
+        json!({ "jsonrpc": "2.0", "id": 5, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ];
+    let messages = serve(&tempdir, &requests);
+
+    let [
+        initialize,
+        diagnostics,
+        range_formatting,
+        code_action,
+        no_code_action,
+        shutdown,
+    ] = messages.as_slice()
+    else {
+        panic!("unexpected messages: {messages:#?}");
+    };
+
+    assert_eq!(
+        json!(true),
+        initialize["result"]["capabilities"]["documentRangeFormattingProvider"]
+    );
+
+    assert_eq!("textDocument/publishDiagnostics", diagnostics["method"]);
+    assert_eq!(
+        1,
+        diagnostics["params"]["diagnostics"]
+            .as_array()
+            .unwrap()
+            .len()
+    );
+    assert_eq!(
+        range(0, 1),
+        diagnostics["params"]["diagnostics"][0]["range"]
+    );
+
+    let text_edit = json!({ "range": range(0, 1), "newText": "/// Needs formatting\n" });
+    assert_eq!(json!([text_edit]), range_formatting["result"]);
+
+    assert_eq!(
+        "Reformat this doc comment",
+        code_action["result"][0]["title"]
+    );
+    assert_eq!(
+        json!([text_edit]),
+        code_action["result"][0]["edit"]["changes"][&uri]
+    );
+
+    assert_eq!(json!([]), no_code_action["result"]);
+
+    assert_eq!(Value::Null, shutdown["result"]);
+}
+
+#[cfg_attr(windows, ignore = "testing on Unix-like platforms is sufficient")]
+#[test]
+fn lsp_crlf() {
+    let tempdir = tempdir().unwrap();
+    let uri = format!("file://{}/lib.rs", tempdir.path().display());
+
+    let requests = [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": {
+                    "uri": uri,
+                    "languageId": "rust",
+                    "version": 1,
+                    "text": SOURCE.replace('\n', "\r\n"),
+                },
+            },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "textDocument/rangeFormatting",
+            "params": {
+                "textDocument": { "uri": uri },
+                "range": range(0, 4),
+                "options": { "tabSize": 4, "insertSpaces": true },
+            },
+        }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ];
+
+    let messages = serve(&tempdir, &requests);
+
+    let [_initialize, _diagnostics, range_formatting, _shutdown] = messages.as_slice() else {
+        panic!("unexpected messages: {messages:#?}");
+    };
+
+    let text_edit = json!({ "range": range(0, 1), "newText": "/// Needs formatting\r\n" });
+    assert_eq!(json!([text_edit]), range_formatting["result"]);
+}
+
+#[cfg_attr(windows, ignore = "testing on Unix-like platforms is sufficient")]
+#[test]
+fn lsp_diagnostics_on_save() {
+    let tempdir = tempdir().unwrap();
+    let uri = format!("file://{}/lib.rs", tempdir.path().display());
+    let changed_source = |docs: &str| format!("{SOURCE}\n{docs}\nfn baz() {{}}\n");
+
+    let requests = [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": SOURCE },
+            },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": uri, "version": 2 },
+                "contentChanges": [{ "text": changed_source("///  Also needs formatting") }],
+            },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": uri, "version": 3 },
+                "contentChanges": [{ "text": changed_source("///  Also  needs formatting") }],
+            },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didSave",
+            "params": { "textDocument": { "uri": uri } },
+        }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ];
+
+    let messages = serve(&tempdir, &requests);
+
+    // smoelius: The changes produce no diagnostics. Only the open and the save do.
+    let [_initialize, opened, saved, _shutdown] = messages.as_slice() else {
+        panic!("unexpected messages: {messages:#?}");
+    };
+
+    assert_eq!("textDocument/publishDiagnostics", opened["method"]);
+    assert_eq!(1, opened["params"]["diagnostics"].as_array().unwrap().len());
+
+    assert_eq!("textDocument/publishDiagnostics", saved["method"]);
+    assert_eq!(2, saved["params"]["diagnostics"].as_array().unwrap().len());
+    assert_eq!(range(6, 7), saved["params"]["diagnostics"][1]["range"]);
+}
+
+fn serve(dir: &TempDir, requests: &[Value]) -> Vec<Value> {
+    let stdin = requests.iter().map(frame).collect::<String>();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.arg("lsp");
+    command.current_dir(dir);
+    command.write_stdin(stdin);
+    let assert = command.assert().success();
+    unframe(str::from_utf8(&assert.get_output().stdout).unwrap())
+}
+
+fn range(start: usize, end: usize) -> Value {
+    json!({
+        "start": { "line": start, "character": 0 },
+        "end": { "line": end, "character": 0 },
+    })
+}
+
+fn frame(message: &Value) -> String {
+    let content = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{content}", content.len())
+}
+
+fn unframe(mut stdout: &str) -> Vec<Value> {
+    let mut messages = Vec::new();
+    while let Some((header, rest)) = stdout.split_once("\r\n\r\n") {
+        let content_length = header
+            .strip_prefix("Content-Length: ")
+            .unwrap()
+            .parse::<usize>()
+            .unwrap();
+        let (content, rest) = rest.split_at(content_length);
+        messages.push(serde_json::from_str(content).unwrap());
+        stdout = rest;
+    }
+    messages
+}
@@ -0,0 +1,55 @@
+use serde_json::Value;
+
+mod util;
+use util::{FORMATTED, Fixture, UNFORMATTED};
+
+#[test]
+fn message_format_json() {
+    let fixture = Fixture::new(&[("a.rs", UNFORMATTED), ("b.rs", FORMATTED)]);
+
+    let assert = fixture
+        .command()
+        .args(["a.rs", "b.rs", "--max-width=80", "--message-format=json"])
+        .assert()
+        .success();
+
+    assert_eq!(
+        [
+            r#"{"changed":[[1,1]],"file":"a.rs","max_width":80,"status":"reformatted"}"#,
+            r#"{"changed":[],"file":"b.rs","max_width":80,"status":"unchanged"}"#,
+        ],
+        records(&assert.get_output().stdout).as_slice()
+    );
+}
+
+#[test]
+fn message_format_json_check() {
+    let fixture = Fixture::new(&[("a.rs", UNFORMATTED)]);
+
+    let assert = fixture
+        .command()
+        .args(["a.rs", "--check", "--message-format", "json"])
+        .assert()
+        .failure()
+        .stderr("Error: 1 file(s) would be reformatted\n");
+
+    assert_eq!(
+        [r#"{"changed":[[1,1]],"file":"a.rs","max_width":null,"status":"would-reformat"}"#],
+        records(&assert.get_output().stdout).as_slice()
+    );
+}
+
+/// Parses each line of `stdout` as a JSON object, and removes the object's `duration`, which
+/// varies from run to run
+fn records(stdout: &[u8]) -> Vec<String> {
+    str::from_utf8(stdout)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let mut value = serde_json::from_str::<Value>(line).unwrap();
+            let duration = value.as_object_mut().unwrap().remove("duration").unwrap();
+            assert!(duration.is_f64());
+            value.to_string()
+        })
+        .collect()
+}
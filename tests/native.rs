@@ -0,0 +1,107 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use elaborate::std::fs::{read_to_string_wc, write_wc};
+use predicates::prelude::*;
+use similar_asserts::SimpleDiff;
+use tempfile::tempdir;
+
+mod util;
+
+#[test]
+fn native_matches_prettier_on_fixtures() {
+    for (fixture, file) in [
+        ("fixtures/clippy_issue_14274", "src/main.rs"),
+        ("fixtures/three_modules", "src/lib.rs"),
+    ] {
+        let (_tempdir, path) = util::copy_into_tempdir(fixture).unwrap();
+
+        let mut command = cargo_bin_cmd!("rustdoc-prettier");
+        command.args(["--backend", "native", file]);
+        // smoelius: The native backend does not need `prettier`.
+        command.env("PATH", "");
+        command.current_dir(&path);
+        command.assert().success();
+
+        let contents_expected =
+            read_to_string_wc(path.join(file.replace(".rs", ".expected.rs"))).unwrap();
+        let contents_actual = read_to_string_wc(path.join(file)).unwrap();
+        assert!(
+            contents_expected == contents_actual,
+            "{}",
+            SimpleDiff::from_str(&contents_expected, &contents_actual, "expected", "actual")
+        );
+    }
+}
+
+#[test]
+fn native_dogfood_with_check() {
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["--backend=native", "src/**/*.rs", "--check"]);
+    command.assert().success();
+}
+
+// smoelius: The differences tested here are documented in the README's "Native backend" section.
+#[test]
+fn native_differences_from_prettier() {
+    const SOURCE: &str = "\
+/// *Emphasis* and __strong emphasis__ are left as written, as are `*` list markers and tables:
+///
+/// * An item
+/// * Another item
+///
+/// | a | b |
+/// |---|---|
+/// | c | d |
+///
+/// ```
+/// let this_line_is_in_a_code_block = \"and so is not wrapped, even though it is much too long\";
+/// ```
+fn foo() {}
+";
+
+    const EXPECTED: &str = "\
+/// *Emphasis* and __strong emphasis__ are left as written, as are `*` list
+/// markers and tables:
+///
+/// * An item
+/// * Another item
+///
+/// | a | b |
+/// |---|---|
+/// | c | d |
+///
+/// ```
+/// let this_line_is_in_a_code_block = \"and so is not wrapped, even though it is much too long\";
+/// ```
+fn foo() {}
+";
+
+    let tempdir = tempdir().unwrap();
+    write_wc(tempdir.path().join("lib.rs"), SOURCE).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["--backend", "native", "--max-width", "80", "lib.rs"]);
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    assert_eq!(
+        EXPECTED,
+        read_to_string_wc(tempdir.path().join("lib.rs")).unwrap()
+    );
+}
+
+#[test]
+fn native_rejects_prettier_options() {
+    let tempdir = tempdir().unwrap();
+    write_wc(
+        tempdir.path().join("lib.rs"),
+        "/// Formatted\nfn foo() {}\n",
+    )
+    .unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["--backend", "native", "--no-semi", "lib.rs"]);
+    command.current_dir(&tempdir);
+    command.assert().failure().stderr(predicate::str::contains(
+        "`--no-semi` is not supported by the native backend",
+    ));
+}
@@ -0,0 +1,36 @@
+use elaborate::std::{fs::create_dir_all_wc, path::PathContext};
+
+mod util;
+use util::{FORMATTED, Fixture, UNFORMATTED};
+
+#[test]
+fn out_dir() {
+    let fixture = Fixture::new(&[]);
+    create_dir_all_wc(fixture.path("src")).unwrap();
+    fixture.write("src/a.rs", UNFORMATTED);
+    fixture.write("src/b.rs", FORMATTED);
+
+    fixture
+        .command()
+        .args(["--out-dir", "out", "src/*.rs"])
+        .assert()
+        .success();
+
+    assert_eq!(UNFORMATTED, fixture.read("src/a.rs"));
+    assert_eq!(FORMATTED, fixture.read("out/src/a.rs"));
+    assert!(!fixture.path("out/src/b.rs").try_exists_wc().unwrap());
+}
+
+#[test]
+fn copy_unchanged() {
+    let fixture = Fixture::new(&[("a.rs", UNFORMATTED), ("b.rs", FORMATTED)]);
+
+    fixture
+        .command()
+        .args(["--out-dir=out", "--copy-unchanged", "a.rs", "b.rs"])
+        .assert()
+        .success();
+
+    assert_eq!(FORMATTED, fixture.read("out/a.rs"));
+    assert_eq!(FORMATTED, fixture.read("out/b.rs"));
+}
@@ -0,0 +1,89 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use elaborate::std::{
+    fs::{create_dir_all_wc, write_wc},
+    path::PathContext,
+};
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+mod util;
+
+/// A stand-in for `prettier` that leaves its input unchanged, and that creates a file named `used`
+/// in its directory when run
+const PRETTIER: &str = "\
+#!/bin/sh
+if [ \"$1\" = --version ]; then
+    echo 3.0.0
+    exit
+fi
+touch \"$(dirname \"$0\")/used\"
+cat
+";
+
+#[test]
+fn node_modules_nearest_source_file() {
+    let tempdir = tempdir().unwrap();
+    let project = tempdir.path().join("project");
+    let bin = project.join("node_modules/.bin");
+    create_dir_all_wc(&bin).unwrap();
+    create_dir_all_wc(project.join("src")).unwrap();
+    util::write_script(&bin.join("prettier"), PRETTIER);
+    write_wc(project.join("src/lib.rs"), "/// Formatted\nfn foo() {}\n").unwrap();
+
+    // smoelius: The current directory has no `node_modules`. So the `node_modules` directory must
+    // be found by walking up from the source file.
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.arg("project/src/lib.rs");
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    assert!(bin.join("used").try_exists_wc().unwrap());
+}
+
+#[test]
+fn version_reports_chosen_prettier() {
+    let tempdir = tempdir().unwrap();
+    let dir = tempdir.path().canonicalize_wc().unwrap();
+    for name in ["node_modules/.bin", "environment_variable", "option"] {
+        create_dir_all_wc(dir.join(name)).unwrap();
+        util::write_script(&dir.join(name).join("prettier"), PRETTIER);
+    }
+
+    assert_version(
+        &dir,
+        &[],
+        &format!(
+            "prettier: {}/node_modules/.bin/prettier (found via `node_modules/.bin`)\n",
+            dir.display()
+        ),
+    );
+
+    assert_version(
+        &dir,
+        &[("PRETTIER", "environment_variable/prettier")],
+        "prettier: environment_variable/prettier (found via the `PRETTIER` environment variable)\n",
+    );
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["--version", "--prettier-path", "option/prettier"]);
+    command.env("PRETTIER", "environment_variable/prettier");
+    command.current_dir(&dir);
+    command.assert().success().stdout(predicate::str::ends_with(
+        "prettier: option/prettier (found via `--prettier-path`)\n",
+    ));
+}
+
+fn assert_version(dir: &Path, envs: &[(&str, &str)], expected: &str) {
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.arg("--version");
+    command.env_remove("PRETTIER");
+    command.envs(envs.iter().copied());
+    command.current_dir(dir);
+    command
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with(expected));
+}
@@ -0,0 +1,131 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use elaborate::std::fs::write_wc;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+mod util;
+
+/// A stand-in for `prettier` 3.0.0 that leaves its input unchanged
+const PRETTIER: &str = "\
+#!/bin/sh
+if [ \"$1\" = --version ]; then
+    echo 3.0.0
+    exit
+fi
+cat
+";
+
+#[test]
+fn prettier_version() {
+    let tempdir = tempdir().unwrap();
+    util::write_script(&tempdir.path().join("prettier"), PRETTIER);
+    write_wc(
+        tempdir.path().join("lib.rs"),
+        "/// Formatted\nfn foo() {}\n",
+    )
+    .unwrap();
+
+    write_wc(
+        tempdir.path().join("rustdoc-prettier.toml"),
+        "prettier = \"^3\"\n",
+    )
+    .unwrap();
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["--prettier-path", "./prettier", "lib.rs"]);
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    write_wc(
+        tempdir.path().join("rustdoc-prettier.toml"),
+        "prettier = \"^3.3\"\n",
+    )
+    .unwrap();
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["--prettier-path", "./prettier", "lib.rs"]);
+    command.current_dir(&tempdir);
+    command.assert().failure().stderr(predicate::str::contains(
+        "`./prettier` is prettier 3.0.0, but the configuration file requires prettier `^3.3`",
+    ));
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args([
+        "--prettier-path",
+        "./prettier",
+        "--ignore-prettier-version",
+        "lib.rs",
+    ]);
+    command.current_dir(&tempdir);
+    command.assert().success();
+}
+
+#[test]
+fn prettier_version_file_without_doc_comments() {
+    let fixture = util::Fixture::new(&[
+        ("rustdoc-prettier.toml", "prettier = \"^3.3\"\n"),
+        ("lib.rs", "fn foo() {}\n"),
+    ]);
+    util::write_script(&fixture.path("prettier"), PRETTIER);
+
+    fixture
+        .command()
+        .args(["--prettier-path", "./prettier", "--check", "lib.rs"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "`./prettier` is prettier 3.0.0, but the configuration file requires prettier `^3.3`",
+        ));
+}
+
+#[test]
+fn invalid_prettier_version_requirement() {
+    let tempdir = tempdir().unwrap();
+    write_wc(
+        tempdir.path().join("rustdoc-prettier.toml"),
+        "prettier = \"three\"\n",
+    )
+    .unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.arg("lib.rs");
+    command.current_dir(&tempdir);
+    command.assert().failure().stderr(predicate::str::contains(
+        "failed to parse `prettier` version requirement `three`",
+    ));
+}
+
+#[test]
+fn prettier_version_nearest_source_file() {
+    use elaborate::std::fs::create_dir_all_wc;
+
+    let tempdir = tempdir().unwrap();
+    write_wc(
+        tempdir.path().join("rustdoc-prettier.toml"),
+        "prettier = \"^3.3\"\n",
+    )
+    .unwrap();
+
+    let bin = tempdir.path().join("node_modules/.bin");
+    create_dir_all_wc(&bin).unwrap();
+    util::write_script(&bin.join("prettier"), &PRETTIER.replace("3.0.0", "3.3.0"));
+
+    // smoelius: The `prettier` nearest `sub/lib.rs` does not satisfy the requirement, though the
+    // one nearest the current directory does.
+    let sub_bin = tempdir.path().join("sub/node_modules/.bin");
+    create_dir_all_wc(&sub_bin).unwrap();
+    util::write_script(&sub_bin.join("prettier"), PRETTIER);
+    write_wc(
+        tempdir.path().join("sub/lib.rs"),
+        "/// Formatted\nfn foo() {}\n",
+    )
+    .unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.env_remove("PRETTIER");
+    command.arg("sub/lib.rs");
+    command.current_dir(&tempdir);
+    command.assert().failure().stderr(predicate::str::contains(
+        "is prettier 3.0.0, but the configuration file requires prettier `^3.3`",
+    ));
+}
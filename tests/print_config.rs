@@ -0,0 +1,74 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use elaborate::std::{
+    fs::{create_dir_all_wc, write_wc},
+    path::PathContext,
+};
+use tempfile::tempdir;
+
+mod util;
+
+/// A stand-in for `prettier` that finds a configuration file named `.prettierrc`
+const PRETTIER: &str = "\
+#!/bin/sh
+case \"$1\" in
+    --version) echo 3.0.0 ;;
+    --find-config-path) echo .prettierrc ;;
+    *) cat ;;
+esac
+";
+
+const SOURCE: &str = "\
+//! Crate
+
+/// Foo
+fn foo() {}
+
+mod bar {
+    /// Bar
+    fn bar() {}
+}
+";
+
+#[test]
+fn print_config() {
+    let tempdir = tempdir().unwrap();
+    let dir = tempdir.path().canonicalize_wc().unwrap();
+    util::write_script(&dir.join("prettier"), PRETTIER);
+    create_dir_all_wc(dir.join("src")).unwrap();
+    write_wc(dir.join("src/lib.rs"), SOURCE).unwrap();
+    write_wc(dir.join("rustfmt.toml"), "comment_width = 80\n").unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args([
+        "--print-config",
+        "src/lib.rs",
+        "--prettier-path",
+        "./prettier",
+        "--plugin",
+        "x",
+    ]);
+    command.current_dir(&dir);
+    command.assert().success().stdout(format!(
+        "\
+rustdoc-prettier.toml: none found
+rustfmt.toml: {}/rustfmt.toml
+max width: 79, from `comment_width \
+         = 80`; 1 is subtracted to work around https://github.com/rust-lang/rustfmt/issues/6180
+backend: prettier
+prettier: ./prettier (found via `--prettier-path`)
+prettier config: .prettierrc
+`//!` at indent 0: [\"./prettier\", \"--parser=markdown\", \"--stdin-filepath\", \
+         \"src/lib.rs.rustdoc.md\", \"--prose-wrap=always\", \"--print-width=75\", \"--plugin\", \
+         \"x\"]
+`///` at indent 0: [\"./prettier\", \"--parser=markdown\", \"--stdin-filepath\", \
+         \"src/lib.rs.rustdoc.md\", \"--prose-wrap=always\", \"--print-width=75\", \"--plugin\", \
+         \"x\"]
+`///` at indent 4: [\"./prettier\", \"--parser=markdown\", \"--stdin-filepath\", \
+         \"src/lib.rs.rustdoc.md\", \"--prose-wrap=always\", \"--print-width=71\", \"--plugin\", \
+         \"x\"]
+",
+        dir.display()
+    ));
+}
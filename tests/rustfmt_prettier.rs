@@ -0,0 +1,51 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use elaborate::std::fs::read_to_string_wc;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+mod util;
+
+#[test]
+fn rustfmt_prettier_stdin() {
+    let tempdir = tempdir().unwrap();
+
+    let mut command = cargo_bin_cmd!("rustfmt-prettier");
+    command.args(["--edition", "2024"]);
+    command.current_dir(&tempdir);
+    command.write_stdin("///  Needs formatting\nfn  main( ) {}\n");
+    command
+        .assert()
+        .success()
+        .stdout(predicate::eq("/// Needs formatting\nfn main() {}\n"));
+}
+
+#[test]
+fn rustfmt_prettier_stdin_with_check() {
+    let tempdir = tempdir().unwrap();
+
+    let mut command = cargo_bin_cmd!("rustfmt-prettier");
+    command.args(["--edition", "2024", "--check"]);
+    command.current_dir(&tempdir);
+    command.write_stdin("///  Needs formatting\nfn main() {}\n");
+    command
+        .assert()
+        .failure()
+        .stdout(predicate::eq("Diff in <stdin> at line 1\n"));
+}
+
+#[test]
+fn rustfmt_prettier_formats_module_files() {
+    let (_tempdir, path) = util::copy_into_tempdir("fixtures/globstar").unwrap();
+
+    let mut command = cargo_bin_cmd!("rustfmt-prettier");
+    command.args(["--edition", "2024", "src/lib.rs"]);
+    command.current_dir(&path);
+    command.assert().success();
+
+    let contents = read_to_string_wc(path.join("src/needs_formatting/mod.rs")).unwrap();
+    assert_eq!("//! Needs formatting\n", contents);
+
+    // smoelius: `.hidden.rs` is not a module of `lib.rs`.
+    let contents = read_to_string_wc(path.join("src/.hidden.rs")).unwrap();
+    assert_eq!("//!  Needs formatting\n", contents);
+}
@@ -0,0 +1,85 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use elaborate::std::{
+    fs::{read_to_string_wc, write_wc},
+    process::CommandContext,
+};
+use std::{path::Path, process::Command};
+use tempfile::tempdir;
+
+mod util;
+use util::git;
+
+const SOURCE: &str = "\
+///  Needs formatting
+fn foo() {}
+";
+
+const FORMATTED: &str = "\
+/// Needs formatting
+fn foo() {}
+";
+
+#[test]
+fn staged() {
+    let tempdir = tempdir().unwrap();
+    git(tempdir.path(), &["init", "--quiet"]);
+
+    // smoelius: `a.rs` is staged in its entirety. `b.rs` has unstaged changes. `c.rs` is not
+    // staged at all.
+    write_wc(tempdir.path().join("a.rs"), SOURCE).unwrap();
+    write_wc(tempdir.path().join("b.rs"), SOURCE).unwrap();
+    write_wc(tempdir.path().join("c.rs"), SOURCE).unwrap();
+    git(tempdir.path(), &["add", "a.rs", "b.rs"]);
+    let b_unstaged = format!("{SOURCE}\n///  Unstaged\nfn bar() {{}}\n");
+    write_wc(tempdir.path().join("b.rs"), &b_unstaged).unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.arg("--staged");
+    command.current_dir(&tempdir);
+    command
+        .assert()
+        .success()
+        .stderr("Warning: `b.rs` has unstaged changes; only its staged version was formatted\n");
+
+    assert_eq!(FORMATTED, staged_contents(tempdir.path(), "a.rs"));
+    assert_eq!(
+        FORMATTED,
+        read_to_string_wc(tempdir.path().join("a.rs")).unwrap()
+    );
+
+    assert_eq!(FORMATTED, staged_contents(tempdir.path(), "b.rs"));
+    assert_eq!(
+        b_unstaged,
+        read_to_string_wc(tempdir.path().join("b.rs")).unwrap()
+    );
+
+    assert_eq!(
+        SOURCE,
+        read_to_string_wc(tempdir.path().join("c.rs")).unwrap()
+    );
+}
+
+#[test]
+fn staged_check() {
+    let tempdir = tempdir().unwrap();
+    git(tempdir.path(), &["init", "--quiet"]);
+    write_wc(tempdir.path().join("a.rs"), SOURCE).unwrap();
+    git(tempdir.path(), &["add", "a.rs"]);
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["--staged", "--check"]);
+    command.current_dir(&tempdir);
+    command.assert().failure();
+
+    assert_eq!(SOURCE, staged_contents(tempdir.path(), "a.rs"));
+}
+
+fn staged_contents(dir: &Path, path: &str) -> String {
+    let output = Command::new("git")
+        .args(["show", &format!(":{path}")])
+        .current_dir(dir)
+        .output_wc()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
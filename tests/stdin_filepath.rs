@@ -0,0 +1,73 @@
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use elaborate::std::{
+    fs::{create_dir_all_wc, read_to_string_wc, write_wc},
+    path::PathContext,
+};
+use tempfile::tempdir;
+
+mod util;
+use util::git;
+
+/// A stand-in for `prettier` that leaves its input unchanged, and that appends its arguments to a
+/// file named `args` in its directory
+const PRETTIER: &str = "\
+#!/bin/sh
+if [ \"$1\" = --version ]; then
+    echo 3.0.0
+    exit
+fi
+echo \"$@\" >> \"$(dirname \"$0\")/args\"
+cat
+";
+
+#[test]
+fn stdin_filepath() {
+    let tempdir = tempdir().unwrap();
+    util::write_script(&tempdir.path().join("prettier"), PRETTIER);
+    create_dir_all_wc(tempdir.path().join("src")).unwrap();
+    write_wc(
+        tempdir.path().join("src/lib.rs"),
+        "/// Formatted\nfn foo() {}\n",
+    )
+    .unwrap();
+    write_wc(tempdir.path().join("rustfmt.toml"), "max_width = 100\n").unwrap();
+
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["--prettier-path", "./prettier", "src/lib.rs"]);
+    command.current_dir(&tempdir);
+    command.assert().success();
+
+    assert_eq!(
+        "--parser=markdown --stdin-filepath src/lib.rs.rustdoc.md --prose-wrap=always \
+         --print-width=96\n",
+        read_to_string_wc(tempdir.path().join("args")).unwrap()
+    );
+}
+
+#[test]
+fn stdin_filepath_staged_from_subdirectory() {
+    let tempdir = tempdir().unwrap();
+    let dir = tempdir.path().canonicalize_wc().unwrap();
+    util::write_script(&dir.join("prettier"), PRETTIER);
+    create_dir_all_wc(dir.join("src")).unwrap();
+    write_wc(dir.join("src/lib.rs"), "/// Formatted\nfn foo() {}\n").unwrap();
+    git(&dir, &["init", "--quiet"]);
+    git(&dir, &["add", "src/lib.rs"]);
+
+    // smoelius: The staged file's path is relative to the repository's root, not to the current
+    // directory.
+    let mut command = cargo_bin_cmd!("rustdoc-prettier");
+    command.args(["--staged", "--prettier-path", "../prettier"]);
+    command.current_dir(dir.join("src"));
+    command.assert().success();
+
+    assert_eq!(
+        format!(
+            "--parser=markdown --stdin-filepath {}/src/lib.rs.rustdoc.md\n",
+            dir.display()
+        ),
+        read_to_string_wc(dir.join("args")).unwrap()
+    );
+}
@@ -0,0 +1,40 @@
+use serde_json::Value;
+
+mod util;
+use util::{FORMATTED, Fixture, UNFORMATTED};
+
+/// Invalid UTF-8, which causes formatting to fail
+const INVALID: &[u8] = b"/// \xff\nfn bar() {}\n";
+
+#[test]
+fn transaction() {
+    for (no_transaction, expected_status, expected_contents) in [
+        (false, "would-reformat", UNFORMATTED),
+        (true, "reformatted", FORMATTED),
+    ] {
+        let fixture = Fixture::new(&[("a.rs", UNFORMATTED)]);
+        fixture.write("b.rs", INVALID);
+
+        let mut command = fixture.command();
+        command.args(["a.rs", "b.rs", "--message-format=json"]);
+        if no_transaction {
+            command.arg("--no-transaction");
+        }
+        let assert = command
+            .assert()
+            .failure()
+            .stderr("Error: failed to format 1 file(s)\n");
+
+        let stdout = str::from_utf8(&assert.get_output().stdout).unwrap();
+        let statuses = stdout
+            .lines()
+            .map(|line| {
+                let value = serde_json::from_str::<Value>(line).unwrap();
+                value["status"].as_str().unwrap().to_owned()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!([expected_status, "error"], statuses.as_slice());
+
+        assert_eq!(expected_contents, fixture.read("a.rs"));
+    }
+}
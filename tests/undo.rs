@@ -0,0 +1,40 @@
+use predicates::prelude::*;
+
+mod util;
+use util::{FORMATTED, Fixture, UNFORMATTED};
+
+#[test]
+fn undo() {
+    let fixture = Fixture::new(&[("a.rs", UNFORMATTED), ("b.rs", UNFORMATTED)]);
+
+    fixture.command().args(["a.rs", "b.rs"]).assert().success();
+
+    // smoelius: A file that changed since the run is not restored.
+    let modified = format!("{FORMATTED}\nfn bar() {{}}\n");
+    fixture.write("b.rs", &modified);
+
+    fixture
+        .command()
+        .arg("--undo")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("b.rs"));
+
+    assert_eq!(UNFORMATTED, fixture.read("a.rs"));
+    assert_eq!(modified, fixture.read("b.rs"));
+
+    // smoelius: Once the change is reverted, the file can be restored.
+    fixture.write("b.rs", FORMATTED);
+
+    fixture.command().arg("--undo").assert().success();
+
+    assert_eq!(UNFORMATTED, fixture.read("b.rs"));
+
+    // smoelius: There is nothing left to undo.
+    fixture
+        .command()
+        .arg("--undo")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("found no run to undo"));
+}
@@ -2,8 +2,12 @@
 #![allow(dead_code)]
 
 use anyhow::{Context, Result, ensure};
-use assert_cmd::assert::Assert;
-use elaborate::std::{path::PathContext, process::CommandContext};
+use assert_cmd::{assert::Assert, cargo::cargo_bin_cmd};
+use elaborate::std::{
+    fs::{read_to_string_wc, write_wc},
+    path::PathContext,
+    process::CommandContext,
+};
 use methodify::methodify;
 use std::{
     path::{Path, PathBuf},
@@ -11,6 +15,59 @@ use std::{
 };
 use tempfile::{TempDir, tempdir};
 
+/// A source file whose doc comment needs formatting
+pub const UNFORMATTED: &str = "\
+///  Needs formatting
+fn foo() {}
+";
+
+/// [`UNFORMATTED`] after formatting
+pub const FORMATTED: &str = "\
+/// Needs formatting
+fn foo() {}
+";
+
+/// A temporary directory in which to run rustdoc-prettier
+pub struct Fixture {
+    pub tempdir: TempDir,
+}
+
+impl Fixture {
+    /// Creates a temporary directory containing `files`, which are pairs of paths and contents
+    #[must_use]
+    pub fn new(files: &[(&str, &str)]) -> Self {
+        let fixture = Self {
+            tempdir: tempdir().unwrap(),
+        };
+        for &(path, contents) in files {
+            fixture.write(path, contents);
+        }
+        fixture
+    }
+
+    #[must_use]
+    pub fn path(&self, path: &str) -> PathBuf {
+        self.tempdir.path().join(path)
+    }
+
+    pub fn write(&self, path: &str, contents: impl AsRef<[u8]>) {
+        write_wc(self.path(path), contents).unwrap();
+    }
+
+    #[must_use]
+    pub fn read(&self, path: &str) -> String {
+        read_to_string_wc(self.path(path)).unwrap()
+    }
+
+    /// Returns a rustdoc-prettier command that runs in the temporary directory
+    #[must_use]
+    pub fn command(&self) -> assert_cmd::Command {
+        let mut command = cargo_bin_cmd!("rustdoc-prettier");
+        command.current_dir(&self.tempdir);
+        command
+    }
+}
+
 pub fn copy_into_tempdir(from: impl AsRef<Path>) -> Result<(TempDir, PathBuf)> {
     let from = from.as_ref();
     let filename = from.file_name_wc()?;
@@ -29,6 +86,41 @@ pub fn copy_into(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
     Ok(())
 }
 
+/// Runs git in `dir` with `args`, with settings that allow commits to be made
+pub fn git(dir: &Path, args: &[&str]) {
+    let status = git_command(dir).args(args).status_wc().unwrap();
+    assert!(status.success());
+}
+
+/// Returns a git command that runs in `dir`, with settings that allow commits to be made
+#[must_use]
+pub fn git_command(dir: &Path) -> Command {
+    let mut command = Command::new("git");
+    command
+        .args([
+            "-c",
+            "user.name=rustdoc-prettier",
+            "-c",
+            "user.email=rustdoc-prettier@example.com",
+            "-c",
+            "commit.gpgsign=false",
+        ])
+        .current_dir(dir);
+    command
+}
+
+/// Writes `contents` to the file at `path`, and makes the file executable
+#[cfg(unix)]
+pub fn write_script(path: &Path, contents: &str) {
+    use elaborate::std::fs::{metadata_wc, set_permissions_wc, write_wc};
+    use std::os::unix::fs::PermissionsExt;
+
+    write_wc(path, contents).unwrap();
+    let mut permissions = metadata_wc(path).unwrap().permissions();
+    permissions.set_mode(0o755);
+    set_permissions_wc(path, permissions).unwrap();
+}
+
 pub fn dirty(path: impl AsRef<Path>) -> Option<String> {
     let output = Command::new("git")
         .args(["diff", "--exit-code"])
@@ -0,0 +1,63 @@
+#![cfg(target_os = "linux")]
+
+use assert_cmd::cargo::cargo_bin;
+use elaborate::std::{
+    fs::{read_to_string_wc, write_wc},
+    process::{ChildContext, CommandContext},
+};
+use std::{
+    path::Path,
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+use tempfile::tempdir;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[test]
+fn watch() {
+    let tempdir = tempdir().unwrap();
+    let path = tempdir.path().join("a.rs");
+    write_wc(&path, "///  Needs formatting\n").unwrap();
+
+    let _child = KillOnDrop(
+        Command::new(cargo_bin!("rustdoc-prettier"))
+            .args(["--watch", "a.rs"])
+            .current_dir(&tempdir)
+            .stderr(Stdio::null())
+            .spawn_wc()
+            .unwrap(),
+    );
+
+    wait_for_contents(&path, "/// Needs formatting\n");
+
+    write_wc(&path, "///  Needs formatting again\n").unwrap();
+
+    wait_for_contents(&path, "/// Needs formatting again\n");
+}
+
+/// Kills the child process when dropped, so that the process does not outlive a failed test
+struct KillOnDrop(Child);
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        let _: Result<_, _> = self.0.kill_wc();
+        let _: Result<_, _> = self.0.wait_wc();
+    }
+}
+
+fn wait_for_contents(path: &Path, expected: &str) {
+    let instant = Instant::now();
+    loop {
+        let contents = read_to_string_wc(path).unwrap();
+        if contents == expected {
+            return;
+        }
+        assert!(
+            instant.elapsed() < TIMEOUT,
+            "timed out waiting for {expected:?}; found {contents:?}"
+        );
+        thread::sleep(Duration::from_millis(100));
+    }
+}